@@ -0,0 +1,1042 @@
+#![deny(missing_docs)]
+
+use core::cell::{Cell, RefCell};
+use core::cmp::Ordering;
+use core::ops::ControlFlow;
+
+use crate::cycle_sort::{cycle_impl_hooked, Hook, Stopped};
+use crate::{cycle_sort, predict_writes, CycleSortError};
+
+/// The point at which a budgeted sort was interrupted.
+///
+/// Returned by [`cycle_sort_budget_start`] and passed to
+/// [`cycle_sort_budget_resume`] to continue sorting the same slice.
+///
+/// [`cycle_sort_budget_start`]: fn.cycle_sort_budget_start.html
+/// [`cycle_sort_budget_resume`]: fn.cycle_sort_budget_resume.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SortProgress {
+    src: usize,
+    writes: usize,
+}
+
+impl SortProgress {
+    /// Returns the number of leading elements already in their final
+    /// position.
+    #[inline]
+    pub fn sorted_len(&self) -> usize {
+        self.src
+    }
+
+    /// Returns the total number of writes made so far.
+    #[inline]
+    pub fn writes(&self) -> usize {
+        self.writes
+    }
+}
+
+/// Stops a sort before it would make more than `max` writes.
+struct Budget {
+    max: usize,
+    writes: usize,
+}
+
+impl Hook for Budget {
+    #[inline]
+    fn write(&mut self, src: usize, _from: usize, to: usize) -> ControlFlow<()> {
+        // unless this write closes the cycle, keep one write in reserve for
+        // putting the element in flight back if the next write doesn't fit
+        let needed = if to == src { 1 } else { 2 };
+
+        if self.writes + needed > self.max {
+            return ControlFlow::Break(());
+        }
+
+        self.writes += 1;
+        ControlFlow::Continue(())
+    }
+}
+
+fn budget_impl<T>(slice: &mut [T], start: usize, max_writes: usize) -> Result<usize, Stopped>
+where
+    T: Ord,
+{
+    let mut budget = Budget {
+        max: max_writes,
+        writes: 0,
+    };
+
+    cycle_impl_hooked(slice, start, &|a, b| a.lt(b), &mut budget)
+}
+
+/// Sorts a slice using the elements' natural ordering, making at most
+/// `max_writes` writes.
+///
+/// Returns `Ok` with the number of writes made if the slice could be
+/// sorted within the budget. Otherwise the sort is stopped before the
+/// budget is exceeded and `Err(CycleSortError::BudgetExceeded)` with the
+/// number of writes made so far is returned. The slice is then left
+/// partially sorted, but still holds every one of its elements exactly
+/// once.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort_budget, CycleSortError};
+/// let mut a = [1, 4, 1, 5, 9, 2];
+///
+/// assert_eq!(cycle_sort_budget(&mut a, 5), Ok(5));
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+///
+/// let mut b = [5, 4, 3, 2, 1];
+/// let     r = cycle_sort_budget(&mut b, 2);
+///
+/// assert_eq!(r, Err(CycleSortError::BudgetExceeded { writes: 2 }));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_budget<T>(slice: &mut [T], max_writes: usize) -> Result<usize, CycleSortError>
+where
+    T: Ord,
+{
+    budget_impl(slice, 0, max_writes).map_err(|stopped| CycleSortError::BudgetExceeded {
+        writes: stopped.writes,
+    })
+}
+
+/// Starts a budgeted sort that can be continued later with
+/// [`cycle_sort_budget_resume`].
+///
+/// Returns `Ok` with the number of writes made if the slice could be
+/// sorted within the budget. Otherwise the sort is stopped before the
+/// budget is exceeded and `Err` with the progress reached is returned.
+///
+/// Interrupting a sort in the middle of a cycle costs one extra write to
+/// put the element in flight back into the slice, which is included in the
+/// budget.
+///
+/// [`cycle_sort_budget_resume`]: fn.cycle_sort_budget_resume.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort_budget_resume, cycle_sort_budget_start};
+/// let mut a = [6, 5, 4, 3, 2, 1];
+///
+/// if let Err(mut progress) = cycle_sort_budget_start(&mut a, 2) {
+///     while cycle_sort_budget_resume(&mut a, &mut progress, 2).is_err() {}
+/// }
+///
+/// assert_eq!(a, [1, 2, 3, 4, 5, 6]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_budget_start<T>(slice: &mut [T], max_writes: usize) -> Result<usize, SortProgress>
+where
+    T: Ord,
+{
+    budget_impl(slice, 0, max_writes).map_err(|stopped| SortProgress {
+        src: stopped.src,
+        writes: stopped.writes,
+    })
+}
+
+/// Continues a budgeted sort from `progress`, making at most `max_writes`
+/// writes.
+///
+/// Returns `Ok` with the number of writes made by this call if the slice
+/// is now sorted, or `Err(CycleSortError::BudgetExceeded)` with the number
+/// of writes made by this call if the budget ran out again, in which case
+/// `progress` is updated.
+///
+/// The slice must be the one `progress` was obtained from, and must not
+/// have been modified in the meantime. Otherwise the result is unspecified,
+/// though every element is still kept exactly once.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort_budget_resume, cycle_sort_budget_start};
+/// let mut a = [3, 1, 2, 6, 4, 5];
+/// let mut progress = cycle_sort_budget_start(&mut a, 2).unwrap_err();
+///
+/// assert!(cycle_sort_budget_resume(&mut a, &mut progress, 10).is_ok());
+/// assert_eq!(a, [1, 2, 3, 4, 5, 6]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_budget_resume<T>(
+    slice: &mut [T],
+    progress: &mut SortProgress,
+    max_writes: usize,
+) -> Result<usize, CycleSortError>
+where
+    T: Ord,
+{
+    match budget_impl(slice, progress.src, max_writes) {
+        Ok(writes) => {
+            progress.src = slice.len();
+            progress.writes += writes;
+            Ok(writes)
+        }
+        Err(stopped) => {
+            progress.src = stopped.src;
+            progress.writes += stopped.writes;
+            Err(CycleSortError::BudgetExceeded {
+                writes: stopped.writes,
+            })
+        }
+    }
+}
+
+/// Forwards every write to a callback that may stop the sort.
+struct Controlled<'a, F>(&'a mut F);
+
+impl<F> Hook for Controlled<'_, F>
+where
+    F: FnMut(usize, usize) -> ControlFlow<()>,
+{
+    #[inline]
+    fn write(&mut self, _src: usize, from: usize, to: usize) -> ControlFlow<()> {
+        (self.0)(from, to)
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering, calling `on_write`
+/// before every write, and returns the number of writes made.
+///
+/// `on_write` receives the position the element is taken from and the
+/// position it is about to be written to. Returning `ControlFlow::Break`
+/// stops the sort without making that write. If the sort is stopped in the
+/// middle of a cycle, one more write is made to put the element in flight
+/// back into the slice, which is counted but not passed to `on_write`. The
+/// slice is then left partially sorted, but still holds every one of its
+/// elements exactly once.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_controlled;
+/// use std::ops::ControlFlow;
+///
+/// let mut a = [1, 4, 1, 5, 9, 2];
+/// let mut moves = Vec::new();
+/// let     w = cycle_sort_controlled(&mut a, &mut |from, to| {
+///     moves.push((from, to));
+///     ControlFlow::Continue(())
+/// });
+///
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+/// assert_eq!(w, 5);
+/// assert_eq!(moves, [(1, 3), (3, 4), (4, 5), (5, 2), (2, 1)]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_controlled<T, F>(slice: &mut [T], on_write: &mut F) -> usize
+where
+    T: Ord,
+    F: FnMut(usize, usize) -> ControlFlow<()>,
+{
+    match cycle_impl_hooked(slice, 0, &|a, b| a.lt(b), &mut Controlled(on_write)) {
+        Ok(writes) => writes,
+        Err(stopped) => stopped.writes,
+    }
+}
+
+/// Stops a sort after `max` steps.
+struct Guard {
+    max: usize,
+    steps: usize,
+}
+
+impl Hook for Guard {
+    // the comparator may be inconsistent or record its failures, and must
+    // only be called by the sort itself
+    const CHECKED: bool = false;
+
+    #[inline]
+    fn step(&mut self) -> ControlFlow<()> {
+        if self.steps == self.max {
+            return ControlFlow::Break(());
+        }
+
+        self.steps += 1;
+        ControlFlow::Continue(())
+    }
+}
+
+/// Sorts a slice using a comparator function, doing at most
+/// `max_iterations` iterations, and returns the number of writes made.
+///
+/// Every comparison step counts as one iteration: checking whether one
+/// element is less than another, which takes one call to `compare`, or
+/// whether two elements are equal, which takes two. A comparator that does
+/// not describe a total order may keep the sort from ever finishing, so
+/// this guards against running forever with untrusted comparison logic.
+///
+/// With a well-behaved comparator, every cycle start and every write is
+/// followed by at most one scan over the rest of the slice, which makes up
+/// to `2 * n` scans of fewer than `n` steps each for `n` elements, and
+/// every write skips fewer than `n` duplicates, taking one more step than
+/// it skips. Sorting therefore never takes more than `4 * n * n`
+/// iterations, and usually takes around `n * n`, which gives a sensible
+/// limit.
+///
+/// If the limit is reached, `Err(CycleSortError::IterationLimit)` is returned
+/// and the slice is left partially sorted, but still holds every one of its
+/// elements exactly once.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort_guarded, CycleSortError};
+/// let mut a = [1, 4, 1, 5, 9, 2];
+///
+/// assert_eq!(cycle_sort_guarded(&mut a, &|a, b| a.cmp(b), 4 * 6 * 6), Ok(5));
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+///
+/// // a comparator that doesn't make sense
+/// let mut b = [1, 2, 3, 4];
+/// let     r = cycle_sort_guarded(&mut b, &|_, _| std::cmp::Ordering::Less, 100);
+///
+/// assert_eq!(r, Err(CycleSortError::IterationLimit));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_guarded<T, F>(
+    slice: &mut [T],
+    compare: &F,
+    max_iterations: usize,
+) -> Result<usize, CycleSortError>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut guard = Guard {
+        max: max_iterations,
+        steps: 0,
+    };
+
+    cycle_impl_hooked(
+        slice,
+        0,
+        &|a, b| compare(a, b) == Ordering::Less,
+        &mut guard,
+    )
+    .map_err(|_| CycleSortError::IterationLimit)
+}
+
+/// Stops a sort as soon as two elements have been found incomparable.
+struct Incomparable<'a>(&'a Cell<bool>);
+
+impl Hook for Incomparable<'_> {
+    // checking compares every pair, which would find incomparable pairs the
+    // sort itself never compares
+    const CHECKED: bool = false;
+
+    #[inline]
+    fn step(&mut self) -> ControlFlow<()> {
+        if self.0.get() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[inline]
+    fn write(&mut self, _src: usize, _from: usize, _to: usize) -> ControlFlow<()> {
+        self.step()
+    }
+}
+
+/// Sorts a slice using a comparator function that describes a partial
+/// order, and returns the number of writes made.
+///
+/// Every pair of elements `compare` is called with must be comparable. If
+/// it returns `None`, the sort is stopped before making any more writes
+/// and `Err(CycleSortError::Comparator)` is returned, leaving the slice
+/// partially sorted, but still holding every one of its elements exactly
+/// once. Pairs that are never compared don't matter, but nearly every
+/// pair is compared while sorting, so in practice all elements must be
+/// comparable, like floats without any NaNs. Elements comparing as equal
+/// are treated as interchangeable, as usual.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort_partial_ord, CycleSortError};
+/// let mut a = [2.5, -1.0, 0.0];
+///
+/// assert_eq!(cycle_sort_partial_ord(&mut a, &|a, b| a.partial_cmp(b)), Ok(3));
+/// assert_eq!(a, [-1.0, 0.0, 2.5]);
+///
+/// let mut b = [2.5, f64::NAN, 0.0];
+/// let     r = cycle_sort_partial_ord(&mut b, &|a, b| a.partial_cmp(b));
+///
+/// assert_eq!(r, Err(CycleSortError::Comparator));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_partial_ord<T, F>(slice: &mut [T], compare: &F) -> Result<usize, CycleSortError>
+where
+    F: Fn(&T, &T) -> Option<Ordering>,
+{
+    let incomparable = Cell::new(false);
+
+    let is_less = |a: &T, b: &T| match compare(a, b) {
+        Some(order) => order == Ordering::Less,
+        None => {
+            incomparable.set(true);
+            false
+        }
+    };
+
+    match cycle_impl_hooked(slice, 0, &is_less, &mut Incomparable(&incomparable)) {
+        // the last comparison may have come after the last check
+        Ok(_) if incomparable.get() => Err(CycleSortError::Comparator),
+        Ok(writes) => Ok(writes),
+        Err(_) => Err(CycleSortError::Comparator),
+    }
+}
+
+/// Sorts a slice using a comparator function that may fail on some pairs of
+/// elements, and returns the number of writes made and the number of
+/// comparisons that failed.
+///
+/// A failed comparison is treated as if the two elements were equal, and
+/// its error is stored in `errors`, in the order the comparisons were made.
+/// Once `errors` is full, further errors are only counted. Treating
+/// ambiguous pairs as equal may keep the comparisons from describing a
+/// total order, in which case the sort is stopped after `4 * n * n`
+/// comparison steps, counted like the iterations of
+/// [`cycle_sort_guarded`], the most a well-behaved comparator ever needs
+/// for `n` elements. Either way the slice is left as sorted as the
+/// successful comparisons allow, holding every one of its elements exactly
+/// once.
+///
+/// [`cycle_sort_guarded`]: fn.cycle_sort_guarded.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_by_lenient;
+/// let compare = |a: &Option<u32>, b: &Option<u32>| match (a, b) {
+///     (Some(a), Some(b)) => Ok(a.cmp(b)),
+///     _ => Err("unknown value"),
+/// };
+///
+/// let mut a = [Some(3), Some(1), Some(2)];
+/// let mut e = [""; 4];
+///
+/// assert_eq!(cycle_sort_by_lenient(&mut a, &compare, &mut e), (3, 0));
+/// assert_eq!(a, [Some(1), Some(2), Some(3)]);
+///
+/// let mut b = [Some(2), None, Some(1)];
+/// let     r = cycle_sort_by_lenient(&mut b, &compare, &mut e);
+///
+/// // `None` is left between the values it couldn't be compared with
+/// assert_eq!(r, (2, 5));
+/// assert_eq!(b, [Some(1), None, Some(2)]);
+/// assert_eq!(e, ["unknown value"; 4]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_by_lenient<T, E, F>(
+    slice: &mut [T],
+    compare: &F,
+    errors: &mut [E],
+) -> (usize, usize)
+where
+    F: Fn(&T, &T) -> Result<Ordering, E>,
+{
+    let length = slice.len();
+    let count = Cell::new(0);
+    let errors = RefCell::new(errors);
+
+    let is_less = |a: &T, b: &T| match compare(a, b) {
+        Ok(order) => order == Ordering::Less,
+        Err(error) => {
+            let i = count.get();
+            if let Some(slot) = errors.borrow_mut().get_mut(i) {
+                *slot = error;
+            }
+            count.set(i + 1);
+            false
+        }
+    };
+
+    let mut guard = Guard {
+        max: length.saturating_mul(length).saturating_mul(4),
+        steps: 0,
+    };
+
+    let writes = match cycle_impl_hooked(slice, 0, &is_less, &mut guard) {
+        Ok(writes) => writes,
+        Err(stopped) => stopped.writes,
+    };
+
+    (writes, count.get())
+}
+
+/// Sorts a slice using the elements' natural ordering, but only if that
+/// takes at most a given fraction of its length in writes.
+///
+/// `max_write_ratio` is the fraction `(numerator, denominator)` of the
+/// slice's length that may be written to. If sorting would make more writes
+/// than that, the slice is left untouched and `None` is returned. Otherwise
+/// the slice is sorted and `Some` with the number of writes made is
+/// returned.
+///
+/// The writes are predicted up front with [`predict_writes`], so this costs
+/// roughly twice as many comparisons as sorting directly.
+///
+/// [`predict_writes`]: fn.predict_writes.html
+///
+/// # Panics
+///
+/// Panics if the denominator is zero.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_if_cheap;
+/// // allow moving up to 30% of the elements
+/// let mut a = [1, 2, 3, 5, 4, 6, 7, 8, 9, 10];
+///
+/// assert_eq!(cycle_sort_if_cheap(&mut a, (3, 10)), Some(2));
+/// assert_eq!(a, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+///
+/// let mut b = [3, 2, 1, 6, 5, 4, 7, 8, 9, 10];
+///
+/// assert_eq!(cycle_sort_if_cheap(&mut b, (3, 10)), None);
+/// assert_eq!(b, [3, 2, 1, 6, 5, 4, 7, 8, 9, 10]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_if_cheap<T>(slice: &mut [T], max_write_ratio: (usize, usize)) -> Option<usize>
+where
+    T: Ord,
+{
+    let (numerator, denominator) = max_write_ratio;
+
+    assert!(denominator != 0, "write ratio denominator must be non-zero");
+
+    let writes = predict_writes(slice);
+
+    if writes as u128 * denominator as u128 > numerator as u128 * slice.len() as u128 {
+        return None;
+    }
+
+    Some(cycle_sort(slice))
+}
+
+/// Sorts a slice using the elements' natural ordering, making at most
+/// `max_moved_permille` writes per thousand elements.
+///
+/// This is the same as [`cycle_sort_budget`], with a budget of
+/// `n * max_moved_permille / 1000` writes, rounded down, for a slice of `n`
+/// elements, computed with integers only. Since a write moves one element,
+/// this limits the fraction of elements moved. Unlike
+/// [`cycle_sort_if_cheap`], this doesn't predict the writes first, but when
+/// the budget would be exceeded, `Err(CycleSortError::BudgetExceeded)` with
+/// the number of writes made so far is returned, and the slice is left
+/// partially sorted, but still holding every one of its elements exactly
+/// once.
+///
+/// [`cycle_sort_budget`]: fn.cycle_sort_budget.html
+/// [`cycle_sort_if_cheap`]: fn.cycle_sort_if_cheap.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_move_ratio;
+/// // allow moving up to 25% of the elements
+/// let mut a = [1, 2, 3, 5, 4, 6, 7, 8, 9, 10];
+///
+/// assert_eq!(cycle_sort_move_ratio(&mut a, 250), Ok(2));
+/// assert_eq!(a, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+///
+/// let mut b = [3, 2, 1, 6, 5, 4, 7, 8, 9, 10];
+///
+/// assert!(cycle_sort_move_ratio(&mut b, 250).is_err());
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_move_ratio<T>(
+    slice: &mut [T],
+    max_moved_permille: u16,
+) -> Result<usize, CycleSortError>
+where
+    T: Ord,
+{
+    let max_writes = slice.len() as u128 * max_moved_permille as u128 / 1000;
+    let max_writes = max_writes.min(usize::MAX as u128) as usize;
+
+    cycle_sort_budget(slice, max_writes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{seq::SliceRandom, thread_rng, Rng};
+
+    #[test]
+    fn large_budget() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            let mut b = a;
+
+            let writes = cycle_sort(&mut a[..length]);
+
+            assert_eq!(cycle_sort_budget(&mut b[..length], writes), Ok(writes));
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn never_exceeds_budget() {
+        const SIZE: usize = 30;
+
+        let mut array = [0; SIZE];
+        let mut rng = thread_rng();
+
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        for max_writes in 0..SIZE {
+            for _ in 0..10 {
+                array.shuffle(&mut rng);
+                let mut sorted = array;
+                sorted.sort();
+
+                match cycle_sort_budget(&mut array, max_writes) {
+                    Ok(writes) => assert!(writes <= max_writes),
+                    Err(CycleSortError::BudgetExceeded { writes }) => {
+                        assert!(writes <= max_writes)
+                    }
+                    Err(error) => panic!("unexpected error {}", error),
+                }
+
+                // no element was lost or duplicated
+                let mut after = array;
+                after.sort();
+                assert_eq!(after, sorted);
+            }
+        }
+    }
+
+    #[test]
+    fn resume_until_sorted() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for max_writes in 2..10 {
+            for _ in 0..10 {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..]);
+                for x in a.iter_mut() {
+                    *x %= 8;
+                }
+                let mut b = a;
+
+                let _ = cycle_sort(&mut a);
+
+                if let Err(mut progress) = cycle_sort_budget_start(&mut b, max_writes) {
+                    while let Err(CycleSortError::BudgetExceeded { writes }) =
+                        cycle_sort_budget_resume(&mut b, &mut progress, max_writes)
+                    {
+                        assert!(writes <= max_writes);
+                    }
+                }
+
+                assert_eq!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let slice = &mut array[..length];
+
+            assert_eq!(cycle_sort_budget(slice, 0), Ok(0));
+            assert_eq!(cycle_sort_budget_start(slice, 0), Ok(0));
+
+            let mut progress = SortProgress::default();
+
+            assert_eq!(cycle_sort_budget_resume(slice, &mut progress, 0), Ok(0));
+            assert_eq!(progress.writes(), 0);
+
+            let writes = cycle_sort_controlled(slice, &mut |_, _| ControlFlow::Break(()));
+
+            assert_eq!(writes, 0);
+            assert_eq!(cycle_sort_guarded(slice, &|a, b| a.cmp(b), 0), Ok(0));
+            assert_eq!(cycle_sort_if_cheap(slice, (0, 1)), Some(0));
+            assert_eq!(cycle_sort_move_ratio(slice, 0), Ok(0));
+            assert_eq!(cycle_sort_partial_ord(slice, &|_, _| None), Ok(0));
+            assert_eq!(
+                cycle_sort_by_lenient(slice, &|_, _| Err(()), &mut []),
+                (0, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn controlled_stop() {
+        const SIZE: usize = 30;
+
+        let mut array = [0; SIZE];
+        let mut rng = thread_rng();
+
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        for limit in 0..SIZE {
+            array.shuffle(&mut rng);
+
+            let mut calls = 0;
+            let writes = cycle_sort_controlled(&mut array, &mut |_, _| {
+                if calls == limit {
+                    return ControlFlow::Break(());
+                }
+                calls += 1;
+                ControlFlow::Continue(())
+            });
+
+            // at most one write is made after stopping
+            assert!(writes == calls || writes == calls + 1);
+
+            let mut after = array;
+            after.sort();
+            assert!(after.iter().enumerate().all(|(i, &x)| i == x));
+        }
+    }
+
+    #[test]
+    fn guarded_bound() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for divisor in &[2, 5, 255] {
+                let mut a = [0_u8; SIZE];
+                let slice = &mut a[..length];
+                rng.fill(&mut slice[..]);
+                for x in slice.iter_mut() {
+                    *x %= divisor;
+                }
+
+                let bound = 4 * length * length;
+
+                assert!(cycle_sort_guarded(slice, &|a, b| a.cmp(b), bound).is_ok());
+                assert!(slice.windows(2).all(|w| w[0] <= w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn lenient_without_errors() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            let mut b = a;
+
+            let expect = cycle_sort(&mut a[..length]);
+            let result =
+                cycle_sort_by_lenient(&mut b[..length], &|a, b| Ok::<_, ()>(a.cmp(b)), &mut []);
+
+            assert_eq!(result, (expect, 0));
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn lenient_with_errors() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        // some pairs fail, which doesn't describe a consistent order
+        let compare = |a: &u8, b: &u8| {
+            if a != b && (a ^ b) & 3 == 0 {
+                Err((*a, *b))
+            } else {
+                Ok(a.cmp(b))
+            }
+        };
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            let mut b = a;
+            let mut errors = [(0, 0); 8];
+
+            let (_, count) = cycle_sort_by_lenient(&mut b[..length], &compare, &mut errors);
+
+            for &(x, y) in &errors[..count.min(errors.len())] {
+                assert!(compare(&x, &y).is_err());
+            }
+
+            // still a permutation of the input
+            a[..length].sort();
+            b[..length].sort();
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn lenient_only_sort_errors() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        let failed = Cell::new(0);
+        let compare = |a: &Option<u8>, b: &Option<u8>| match (a, b) {
+            (Some(a), Some(b)) => Ok(a.cmp(b)),
+            _ => {
+                failed.set(failed.get() + 1);
+                Err(failed.get())
+            }
+        };
+
+        let mut a = [Some(2), None, Some(1)];
+        let mut errors = [0; 8];
+
+        assert_eq!(cycle_sort_by_lenient(&mut a, &compare, &mut errors), (2, 5));
+        assert_eq!(a, [Some(1), None, Some(2)]);
+        assert_eq!(errors, [1, 2, 3, 4, 5, 0, 0, 0]);
+
+        for length in 0..SIZE {
+            let mut a = [None; SIZE];
+            for x in a[..length].iter_mut() {
+                if rng.gen_range(0, 4) != 0 {
+                    *x = Some(rng.gen_range(0, 10));
+                }
+            }
+            let mut errors = [0; SIZE];
+            failed.set(0);
+
+            let (_, count) = cycle_sort_by_lenient(&mut a[..length], &compare, &mut errors);
+
+            // every failed call is recorded in order, and none is made
+            // besides the ones limited by the guard
+            assert_eq!(count, failed.get());
+            assert!(count <= 4 * length * length);
+            for (i, &e) in errors[..count.min(SIZE)].iter().enumerate() {
+                assert_eq!(e, i + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn partial_order() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+            let mut b = a;
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+            let writes = cycle_sort_partial_ord(&mut b[..length], &|a, b| a.partial_cmp(b));
+
+            assert_eq!(writes, Ok(expect));
+            assert_eq!(a, b);
+
+            if length == 0 {
+                continue;
+            }
+
+            // make one element incomparable to every other one
+            let mut c = b;
+            c[rng.gen_range(0, length)] = 10;
+            let mut sorted = c;
+            sorted[..length].sort();
+
+            let writes = cycle_sort_partial_ord(&mut c[..length], &|a, b| {
+                if (*a == 10) != (*b == 10) {
+                    None
+                } else {
+                    a.partial_cmp(b)
+                }
+            });
+
+            if length > 1 {
+                assert_eq!(writes, Err(CycleSortError::Comparator));
+            }
+
+            c[..length].sort();
+            assert_eq!(c, sorted);
+        }
+    }
+
+    #[test]
+    fn partial_order_stops_early() {
+        const SIZE: usize = 40;
+
+        for length in 2..SIZE {
+            let mut a = [0_u8; SIZE];
+            for (i, x) in a.iter_mut().enumerate() {
+                *x = (i * 7 % length) as u8;
+            }
+            a[length - 1] = 255;
+
+            let calls = Cell::new(0);
+            let writes = cycle_sort_partial_ord(&mut a[..length], &|a: &u8, b: &u8| {
+                calls.set(calls.get() + 1);
+                if (*a == 255) != (*b == 255) {
+                    None
+                } else {
+                    a.partial_cmp(b)
+                }
+            });
+
+            // the first scan reaches the incomparable element, and the sort
+            // stops right after
+            assert_eq!(writes, Err(CycleSortError::Comparator));
+            assert_eq!(calls.get(), length - 1);
+        }
+    }
+
+    #[test]
+    fn guarded_calls() {
+        const SIZE: usize = 40;
+
+        for &max_iterations in &[0, 1, 10, 100] {
+            let mut a = [0_u8; SIZE];
+            let calls = Cell::new(0);
+
+            let result = cycle_sort_guarded(
+                &mut a,
+                &|_, _| {
+                    calls.set(calls.get() + 1);
+                    Ordering::Less
+                },
+                max_iterations,
+            );
+
+            // telling whether two elements are equal takes two calls
+            assert_eq!(result, Err(CycleSortError::IterationLimit));
+            assert!(calls.get() <= 2 * max_iterations);
+        }
+
+        // one step finds where the first element belongs, one checks that
+        // it isn't put after a duplicate, with two calls, and one finds
+        // that the other element belongs where the first one was
+        let calls = Cell::new(0);
+        let compare = |a: &i32, b: &i32| {
+            calls.set(calls.get() + 1);
+            a.cmp(b)
+        };
+
+        let mut a = [2, 1];
+
+        assert_eq!(
+            cycle_sort_guarded(&mut a, &compare, 2),
+            Err(CycleSortError::IterationLimit)
+        );
+
+        let mut a = [2, 1];
+        calls.set(0);
+
+        assert_eq!(cycle_sort_guarded(&mut a, &compare, 3), Ok(2));
+        assert_eq!(a, [1, 2]);
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn move_ratio() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for &permille in &[0, 1, 100, 333, 500, 999, 1000, u16::MAX] {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= 4;
+                }
+                let mut b = a;
+
+                let max_writes = length * permille as usize / 1000;
+                let expect = cycle_sort_budget(&mut a[..length], max_writes);
+
+                assert_eq!(cycle_sort_move_ratio(&mut b[..length], permille), expect);
+                assert_eq!(a, b);
+
+                match expect {
+                    Ok(writes) | Err(CycleSortError::BudgetExceeded { writes }) => {
+                        assert!(writes <= max_writes)
+                    }
+                    Err(error) => panic!("unexpected error {}", error),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn if_cheap() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for numerator in 0..5 {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= 4;
+                }
+
+                let before = a;
+                let writes = predict_writes(&a[..length]);
+
+                match cycle_sort_if_cheap(&mut a[..length], (numerator, 4)) {
+                    Some(w) => {
+                        assert_eq!(w, writes);
+                        assert!(4 * writes <= numerator * length);
+                    }
+                    None => {
+                        assert_eq!(a, before);
+                        assert!(4 * writes > numerator * length);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_budget() {
+        let mut a = [2, 1];
+
+        let exceeded = Err(CycleSortError::BudgetExceeded { writes: 0 });
+
+        assert_eq!(cycle_sort_budget(&mut a, 0), exceeded);
+        assert_eq!(cycle_sort_budget(&mut a, 1), exceeded);
+        assert_eq!(a, [2, 1]);
+        assert_eq!(cycle_sort_budget(&mut a, 2), Ok(2));
+    }
+}
@@ -0,0 +1,193 @@
+#![deny(missing_docs)]
+
+/// Returns whether a slice is sorted in strictly increasing order, that
+/// is, sorted and without any two equal elements.
+///
+/// This takes a single pass over the slice. Empty and single-element
+/// slices are sorted and unique.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::is_sorted_unique;
+/// assert!(is_sorted_unique(&[1, 2, 4, 8]));
+/// assert!(!is_sorted_unique(&[1, 2, 2, 8]));
+/// assert!(!is_sorted_unique(&[2, 1]));
+/// assert!(is_sorted_unique::<i32>(&[]));
+/// ```
+#[inline]
+pub fn is_sorted_unique<T>(slice: &[T]) -> bool
+where
+    T: Ord,
+{
+    slice.windows(2).all(|w| w[0] < w[1])
+}
+
+/// Returns the position `value` can be inserted at in a sorted slice,
+/// keeping it sorted.
+///
+/// The position is after any elements equal to `value`, and is found by
+/// binary search. If `sorted` is not sorted, the position returned is
+/// unspecified.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::sorted_insert_pos;
+/// let a = [1, 2, 2, 4];
+///
+/// assert_eq!(sorted_insert_pos(&a, &2), 3);
+/// assert_eq!(sorted_insert_pos(&a, &3), 3);
+/// assert_eq!(sorted_insert_pos(&a, &0), 0);
+/// assert_eq!(sorted_insert_pos(&a, &5), 4);
+/// ```
+#[inline]
+pub fn sorted_insert_pos<T>(sorted: &[T], value: &T) -> usize
+where
+    T: Ord,
+{
+    sorted.partition_point(|x| x <= value)
+}
+
+/// Returns the Kendall tau distance of a slice from its sorted order.
+///
+/// This is the number of discordant pairs, that is pairs of positions
+/// `i < j` with `slice[i] > slice[j]`, also known as the number of
+/// inversions. It is `0` for a sorted slice and `n * (n - 1) / 2` for a
+/// strictly decreasing one of length `n`. Pairs of equal elements are
+/// never discordant. Counting takes `O(n^2)` comparisons.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::kendall_tau;
+/// assert_eq!(kendall_tau(&[1, 2, 3, 4]), 0);
+/// assert_eq!(kendall_tau(&[2, 1, 4, 3]), 2);
+/// assert_eq!(kendall_tau(&[4, 3, 2, 1]), 6);
+/// ```
+#[inline]
+pub fn kendall_tau<T>(slice: &[T]) -> usize
+where
+    T: Ord,
+{
+    slice
+        .iter()
+        .enumerate()
+        .map(|(i, x)| slice[i + 1..].iter().filter(|&y| x > y).count())
+        .sum()
+}
+
+/// Returns the Kendall tau distance of a slice from its sorted order along
+/// with the number of pairs of positions, as `(discordant, total_pairs)`.
+///
+/// `discordant` is what [`kendall_tau`] returns, and `total_pairs` is
+/// `n * (n - 1) / 2` for a slice of length `n`, so dividing the two gives
+/// a distance between `0` and `1` that can be compared across lengths.
+/// An empty or single-element slice has no pairs at all.
+///
+/// [`kendall_tau`]: fn.kendall_tau.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::kendall_tau_normalized;
+/// let (d, t) = kendall_tau_normalized(&[2, 1, 4, 3]);
+///
+/// assert_eq!((d, t), (2, 6));
+/// assert_eq!(d as f64 / t as f64, 1.0 / 3.0);
+/// ```
+#[inline]
+pub fn kendall_tau_normalized<T>(slice: &[T]) -> (usize, usize)
+where
+    T: Ord,
+{
+    let length = slice.len();
+
+    (kendall_tau(slice), length * length.saturating_sub(1) / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_sort_parity;
+
+    use rand::{seq::SliceRandom, thread_rng, Rng};
+
+    #[test]
+    fn sorted_unique() {
+        assert!(is_sorted_unique::<u8>(&[]));
+        assert!(is_sorted_unique(&[0]));
+        assert!(is_sorted_unique(&[0, 1]));
+        assert!(!is_sorted_unique(&[0, 0]));
+        assert!(!is_sorted_unique(&[1, 0]));
+        assert!(!is_sorted_unique(&[0, 1, 2, 2, 3]));
+        assert!(!is_sorted_unique(&[(); 2]));
+        assert!(is_sorted_unique(&[(); 1]));
+    }
+
+    #[test]
+    fn insert_pos() {
+        let a = [1, 3, 3, 3, 5];
+
+        for value in 0..7 {
+            let i = sorted_insert_pos(&a, &value);
+
+            assert!(a[..i].iter().all(|&x| x <= value));
+            assert!(a[i..].iter().all(|&x| x > value));
+        }
+
+        assert_eq!(sorted_insert_pos(&[], &1), 0);
+        assert_eq!(sorted_insert_pos(&[(); 3], &()), 3);
+    }
+
+    #[test]
+    fn kendall_tau_distance() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+            let slice = &mut a[..length];
+
+            let (discordant, total) = kendall_tau_normalized(slice);
+
+            assert_eq!(discordant, kendall_tau(slice));
+            assert_eq!(total, (0..length).sum::<usize>());
+            assert!(discordant <= total);
+
+            // swapping a discordant neighboring pair removes exactly one
+            if let Some(i) = (1..length).find(|&i| slice[i - 1] > slice[i]) {
+                slice.swap(i - 1, i);
+                assert_eq!(kendall_tau(slice), discordant - 1);
+            }
+
+            slice.sort_unstable();
+            assert_eq!(kendall_tau(slice), 0);
+
+            // only ties keep a reversed slice from being fully discordant
+            slice.reverse();
+            let ties: usize = (0..8)
+                .map(|v| slice.iter().filter(|&&x| x == v).count())
+                .map(|n| n * n.saturating_sub(1) / 2)
+                .sum();
+            assert_eq!(kendall_tau(slice), total - ties);
+        }
+
+        // the parity of a permutation is that of its inversions
+        let mut p = [0_usize; SIZE];
+        for (i, x) in p.iter_mut().enumerate() {
+            *x = i;
+        }
+        for _ in 0..20 {
+            p.shuffle(&mut rng);
+            assert_eq!(kendall_tau(&p) % 2 == 1, cycle_sort_parity(&p).1);
+        }
+
+        assert_eq!(kendall_tau_normalized(&[(); 4]), (0, 6));
+    }
+}
@@ -2,9 +2,12 @@
 
 use core::cmp::Ordering;
 use core::mem::{self, ManuallyDrop};
+use core::ops::ControlFlow;
 use core::ptr;
 
-use crate::util;
+#[cfg(feature = "fast-eq")]
+use crate::util::ByOrdering;
+use crate::util::Less;
 
 /// Sorts a slice using the elements' natural ordering and returns the
 /// number of writes made.
@@ -19,6 +22,7 @@ use crate::util;
 /// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
 /// assert_eq!(w, 5);
 /// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
 #[inline]
 pub fn cycle_sort<T>(slice: &mut [T]) -> usize
 where
@@ -30,6 +34,9 @@ where
 /// Sorts a slice using a comparator function and returns the number of
 /// writes made.
 ///
+/// With the `fast-eq` feature, checking whether two elements are equal
+/// takes a single call to `compare` instead of two.
+///
 /// # Examples
 ///
 /// ```
@@ -41,14 +48,51 @@ where
 /// assert_eq!(a, ["demissa", "deltoidea", "decapetala", "davidii", "dahurica"]);
 /// assert_eq!(w, 4);
 /// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
 #[inline]
 pub fn cycle_sort_by<T, F>(slice: &mut [T], compare: &F) -> usize
 where
     F: Fn(&T, &T) -> Ordering,
 {
+    #[cfg(feature = "fast-eq")]
+    return match cycle_impl_ordered(slice, 0, &ByOrdering(compare), &mut ()) {
+        Ok(writes) => writes,
+        Err(_) => unreachable!(),
+    };
+
+    #[cfg(not(feature = "fast-eq"))]
     cycle_impl(slice, &|a, b| compare(a, b) == Ordering::Less)
 }
 
+/// Sorts a slice using a comparator function taken by value and returns
+/// the number of writes made.
+///
+/// This is the same as [`cycle_sort_by`], but takes `compare` like
+/// [`slice::sort_by`] does, which saves borrowing short closures.
+///
+/// [`cycle_sort_by`]: fn.cycle_sort_by.html
+/// [`slice::sort_by`]: https://doc.rust-lang.org/std/primitive.slice.html#method.sort_by
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_by_val;
+/// // reverse sorting
+/// let mut a = [1, 4, 1, 5, 9, 2];
+/// let     w = cycle_sort_by_val(&mut a, |a, b| b.cmp(a));
+///
+/// assert_eq!(a, [9, 5, 4, 2, 1, 1]);
+/// assert_eq!(w, 6);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_by_val<T, F>(slice: &mut [T], compare: F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    cycle_sort_by(slice, &compare)
+}
+
 /// Sorts a slice with a key extraction function and returns the number of
 /// writes made.
 ///
@@ -63,6 +107,7 @@ where
 /// assert_eq!(a, ["zwölf", "zymbel", "zzxjoanw"]);
 /// assert_eq!(w, 2);
 /// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
 #[inline]
 pub fn cycle_sort_by_key<T, F, U>(slice: &mut [T], key: &F) -> usize
 where
@@ -72,70 +117,626 @@ where
     cycle_impl(slice, &|a, b| key(a).lt(&key(b)))
 }
 
-fn cycle_impl<T, F>(slice: &mut [T], is_less: &F) -> usize
+/// Sorts a slice of unique elements using their natural ordering and
+/// returns the number of writes made.
+///
+/// This saves the comparisons [`cycle_sort`] makes to skip over positions
+/// holding duplicates. Passing a slice with equal elements is a logic
+/// error: the slice still ends up sorted, but more writes than necessary
+/// may be made. Debug builds panic after sorting such a slice.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_assume_unique;
+/// let mut a = [3, 1, 4, 5, 9, 2];
+/// let     w = cycle_sort_assume_unique(&mut a);
+///
+/// assert_eq!(a, [1, 2, 3, 4, 5, 9]);
+/// assert_eq!(w, 6);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_assume_unique<T>(slice: &mut [T]) -> usize
 where
-    F: Fn(&T, &T) -> bool,
+    T: Ord,
 {
+    struct Unique;
+
+    impl Hook for Unique {
+        const UNIQUE: bool = true;
+    }
+
+    let writes = match cycle_impl_hooked(slice, 0, &|a: &T, b: &T| a.lt(b), &mut Unique) {
+        Ok(writes) => writes,
+        Err(_) => unreachable!(),
+    };
+
+    debug_assert!(
+        crate::is_sorted_unique(slice),
+        "slice passed to cycle_sort_assume_unique has duplicates"
+    );
+
+    writes
+}
+
+/// Sorts a slice using the elements' natural ordering, using a bounded
+/// amount of stack, and returns the number of writes made.
+///
+/// This is the same as [`cycle_sort`], which never recurses and never
+/// keeps anything on the stack that grows with the length of the slice:
+/// besides the one element being placed, it only holds a few indices and
+/// counters. This function is never inlined, so its frame and those of the
+/// functions it calls can be measured by stack analysis tools, and their
+/// sizes don't depend on the caller. Most code in this crate shares the
+/// same implementation, and so has the same property.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_bounded_stack;
+/// let mut a = [1, 4, 1, 5, 9, 2];
+/// let     w = cycle_sort_bounded_stack(&mut a);
+///
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+/// assert_eq!(w, 5);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline(never)]
+pub fn cycle_sort_bounded_stack<T>(slice: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    cycle_impl(slice, &|a, b| a.lt(b))
+}
+
+/// The most cycle starts [`cycle_sort_blocked`] checks together.
+///
+/// [`cycle_sort_blocked`]: fn.cycle_sort_blocked.html
+const BLOCK_MAX: usize = 64;
+
+/// Sorts a slice using the elements' natural ordering, checking which
+/// elements are in place already a block at a time, and returns the
+/// number of writes made.
+///
+/// Before following a cycle from a position, [`cycle_sort`] scans the
+/// whole rest of the slice to find out where the element there belongs,
+/// which is wasted whenever it belongs where it is. This instead counts
+/// the smaller elements for `block` neighboring positions at once, going
+/// over the rest of the slice in tiles of `block` by `block` elements
+/// that stay in cache, and then only follows cycles from positions whose
+/// elements turned out not to be in place. The counts stay valid for as
+/// long as the element they were made for isn't moved. This pays off for
+/// long slices with many elements in place, and takes extra comparisons
+/// for the others. Blocks of more than 64 positions are treated as blocks
+/// of 64.
+///
+/// The result and number of writes are the same as for [`cycle_sort`].
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Panics
+///
+/// Panics if `block` is zero.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_blocked;
+/// let mut a = [1, 4, 1, 5, 9, 2];
+/// let     w = cycle_sort_blocked(&mut a, 4);
+///
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+/// assert_eq!(w, 5);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_blocked<T>(slice: &mut [T], block: usize) -> usize
+where
+    T: Ord,
+{
+    assert!(block != 0, "block size must be non-zero");
+
     let length = slice.len();
 
-    // check if sorting is necessary
     if mem::size_of::<T>() == 0 || length < 2 {
         return 0;
     }
 
+    let block = block.min(BLOCK_MAX);
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    let writes = checked(slice, &is_less, |slice| {
+        let mut writes = 0;
+        let mut start = 0;
+
+        while start < length - 1 {
+            let end = (start + block).min(length - 1);
+
+            // the number of elements in `slice[start..]` less than each one in
+            // `slice[start..end]`, which stays the same while sorting only
+            // moves elements within `slice[start..]`
+            let mut less = [0; BLOCK_MAX];
+            for tile in slice[start..].chunks(block) {
+                for (count, x) in less.iter_mut().zip(&slice[start..end]) {
+                    *count += tile.iter().filter(|y| is_less(y, x)).count();
+                }
+            }
+
+            let mut hook = Written {
+                start,
+                end,
+                bits: 0,
+            };
+
+            for src in start..end {
+                // an element that hasn't moved is in place if all elements less
+                // than it are before it
+                if hook.bits >> (src - start) & 1 == 0 {
+                    let x = &slice[src];
+                    let before = slice[start..src].iter().filter(|y| is_less(y, x)).count();
+
+                    if less[src - start] == before {
+                        continue;
+                    }
+                }
+
+                let mut tmp = unsafe { ManuallyDrop::new(ptr::read(&slice[src])) };
+                let mut from = src;
+
+                let _ = cycle(
+                    slice,
+                    src,
+                    &mut from,
+                    &mut tmp,
+                    &mut writes,
+                    &is_less,
+                    &mut hook,
+                );
+            }
+
+            start = end;
+        }
+
+        writes
+    });
+
+    #[cfg(feature = "log")]
+    log::trace!("sorted {} elements with {} writes", length, writes);
+
+    writes
+}
+
+/// Marks which positions in `start..end` have been written to.
+struct Written {
+    start: usize,
+    end: usize,
+    bits: u64,
+}
+
+impl Hook for Written {
+    #[inline]
+    fn write(&mut self, _src: usize, _from: usize, to: usize) -> ControlFlow<()> {
+        if to < self.end {
+            self.bits |= 1 << (to - self.start);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// The most elements ever taken out of the slice being sorted at the same
+/// time.
+///
+/// Elements are moved around their cycles through a single temporary,
+/// holding the element displaced by every write until it is written in
+/// turn, so sorting never takes more than one element's worth of memory
+/// besides the slice itself. Elements are compared in place.
+pub const MAX_ELEMENTS_IN_FLIGHT: usize = 1;
+
+fn cycle_impl<T, F>(slice: &mut [T], is_less: &F) -> usize
+where
+    F: Fn(&T, &T) -> bool,
+{
+    if mem::size_of::<T>() == 0 {
+        return 0;
+    }
+
+    let writes = match slice.len() {
+        2 => checked(slice, is_less, |slice| sort2(slice, is_less)),
+        3 => checked(slice, is_less, |slice| sort3(slice, is_less)),
+        _ => return cycle_impl_from(slice, 0, is_less),
+    };
+
+    #[cfg(feature = "log")]
+    log::trace!("sorted {} elements with {} writes", slice.len(), writes);
+
+    writes
+}
+
+/// Traces the writes made moving the element at `positions[0]` to
+/// `positions[1]`, the one there to `positions[2]` and so on, and the last
+/// one back to `positions[0]`, as following a cycle does.
+#[cfg(feature = "log")]
+fn trace_cycle(positions: &[usize]) {
+    log::trace!("cycle started at {}", positions[0]);
+
+    for (i, &from) in positions.iter().enumerate() {
+        log::trace!(
+            "write from {} to {}",
+            from,
+            positions[(i + 1) % positions.len()]
+        );
+    }
+}
+
+/// Sorts a slice of two elements.
+#[inline(always)]
+fn sort2<T, F>(slice: &mut [T], is_less: &F) -> usize
+where
+    F: Fn(&T, &T) -> bool,
+{
+    if is_less(&slice[1], &slice[0]) {
+        #[cfg(feature = "log")]
+        trace_cycle(&[0, 1]);
+
+        slice.swap(0, 1);
+        2
+    } else {
+        0
+    }
+}
+
+/// Sorts a slice of three elements, writing every position at most once.
+///
+/// The order is found with a sorting network on the positions rather than
+/// the elements. Only positions whose element isn't equal to the one they
+/// end up with are then written to, which takes a single swap, or moving
+/// three distinct elements around their cycle.
+#[inline(always)]
+fn sort3<T, F>(slice: &mut [T], is_less: &F) -> usize
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut order = [0, 1, 2];
+
+    for &(i, j) in &[(0, 1), (1, 2), (0, 1)] {
+        if is_less(&slice[order[j]], &slice[order[i]]) {
+            order.swap(i, j);
+        }
+    }
+
+    let equal =
+        |a: usize, b: usize| !is_less(&slice[a], &slice[b]) && !is_less(&slice[b], &slice[a]);
+    let changed = [0, 1, 2].map(|i| order[i] != i && !equal(i, order[i]));
+
+    let swap = |slice: &mut [T], i, j| {
+        #[cfg(feature = "log")]
+        trace_cycle(&[i, j]);
+
+        slice.swap(i, j);
+        2
+    };
+
+    match changed {
+        [false, false, false] => 0,
+        [true, true, false] => swap(slice, 0, 1),
+        [true, false, true] => swap(slice, 0, 2),
+        [false, true, true] => swap(slice, 1, 2),
+        _ => {
+            // `slice[a]` gets the element at `b`, which gets the one at `c`
+            let (a, b, c) = if order == [1, 2, 0] {
+                (0, 1, 2)
+            } else {
+                (0, 2, 1)
+            };
+
+            #[cfg(feature = "log")]
+            trace_cycle(&[a, c, b]);
+
+            unsafe {
+                let p = slice.as_mut_ptr();
+                let tmp = ptr::read(p.add(a));
+                ptr::copy_nonoverlapping(p.add(b), p.add(a), 1);
+                ptr::copy_nonoverlapping(p.add(c), p.add(b), 1);
+                ptr::write(p.add(c), tmp);
+            }
+
+            3
+        }
+    }
+}
+
+/// Sorts `slots[start..]` into place, assuming `slots[..start]` already
+/// holds the `start` smallest elements in order.
+pub(crate) fn cycle_impl_from<S, F>(slots: &mut S, start: usize, is_less: &F) -> usize
+where
+    S: Slots + ?Sized,
+    F: Fn(&S::Item, &S::Item) -> bool,
+{
+    match cycle_impl_hooked(slots, start, is_less, &mut ()) {
+        Ok(writes) => writes,
+        Err(_) => unreachable!(),
+    }
+}
+
+/// Observes the progress of [`cycle_impl_hooked`] and may stop it early.
+pub(crate) trait Hook {
+    /// Whether the elements are known to be unique, so that positions
+    /// holding duplicates need not be skipped.
+    const UNIQUE: bool = false;
+
+    /// Whether the sort may be checked with the `check-permutation` feature,
+    /// which compares every pair of elements before and after sorting. Sorts
+    /// whose comparisons have side effects, or may not describe an order at
+    /// all, opt out.
+    const CHECKED: bool = true;
+
+    /// Called before the element at `src` is taken out to be placed.
+    #[inline(always)]
+    fn start(&mut self, _src: usize) {}
+
+    /// Called before every comparison made while searching for a position.
+    #[inline(always)]
+    fn step(&mut self) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called whenever a position is skipped because it holds a duplicate.
+    #[inline(always)]
+    fn skip(&mut self) {}
+
+    /// Called before the element taken from `from` is written into `to`,
+    /// as part of the cycle starting at `src`.
+    #[inline(always)]
+    fn write(&mut self, _src: usize, _from: usize, _to: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+impl Hook for () {}
+
+/// Where and after how many writes a hooked sort was stopped.
+pub(crate) struct Stopped {
+    pub src: usize,
+    pub writes: usize,
+}
+
+/// Indexable storage that can be sorted by [`cycle_impl_hooked`], allowing
+/// sorting data that is not laid out as a contiguous slice.
+pub(crate) trait Slots {
+    /// The type of the elements being sorted.
+    type Item;
+
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Returns the element at logical position `i`.
+    fn slot(&self, i: usize) -> &Self::Item;
+
+    /// Returns the element at logical position `i` mutably.
+    fn slot_mut(&mut self, i: usize) -> &mut Self::Item;
+}
+
+impl<T> Slots for [T] {
+    type Item = T;
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    #[inline(always)]
+    fn slot(&self, i: usize) -> &T {
+        &self[i]
+    }
+
+    #[inline(always)]
+    fn slot_mut(&mut self, i: usize) -> &mut T {
+        &mut self[i]
+    }
+}
+
+/// Sorts `slots[start..]` into place, assuming `slots[..start]` already
+/// holds the `start` smallest elements in order.
+///
+/// If `hook` stops the sort in the middle of a cycle, the element in flight
+/// is written into the cycle's starting position, which counts as a write.
+/// The slots then hold a valid permutation of their input again, and
+/// sorting can be continued from `Stopped::src`.
+pub(crate) fn cycle_impl_hooked<S, F, H>(
+    slots: &mut S,
+    start: usize,
+    is_less: &F,
+    hook: &mut H,
+) -> Result<usize, Stopped>
+where
+    S: Slots + ?Sized,
+    F: Fn(&S::Item, &S::Item) -> bool,
+    H: Hook,
+{
+    cycle_impl_ordered(slots, start, is_less, hook)
+}
+
+/// Like [`cycle_impl_hooked`], but ordering the elements by any [`Less`],
+/// which may be able to tell equal elements apart more cheaply.
+pub(crate) fn cycle_impl_ordered<S, L, H>(
+    slots: &mut S,
+    start: usize,
+    order: &L,
+    hook: &mut H,
+) -> Result<usize, Stopped>
+where
+    S: Slots + ?Sized,
+    L: Less<S::Item>,
+    H: Hook,
+{
+    let length = slots.len();
+
+    // check if sorting is necessary
+    if mem::size_of::<S::Item>() == 0 || length < 2 {
+        return Ok(0);
+    }
+
+    if H::CHECKED {
+        checked(slots, order, |slots| {
+            cycle_impl_unchecked(slots, start, order, hook)
+        })
+    } else {
+        cycle_impl_unchecked(slots, start, order, hook)
+    }
+}
+
+/// Sorts `slots` with `sort`, and with the `check-permutation` feature,
+/// checks in debug builds that no element was lost or duplicated.
+#[inline(always)]
+#[cfg_attr(
+    not(all(debug_assertions, feature = "check-permutation")),
+    allow(unused_variables)
+)]
+fn checked<S, L, R, F>(slots: &mut S, order: &L, sort: F) -> R
+where
+    S: Slots + ?Sized,
+    L: Less<S::Item>,
+    F: FnOnce(&mut S) -> R,
+{
+    #[cfg(all(debug_assertions, feature = "check-permutation"))]
+    let summary = crate::util::rank_summary(slots, &|a, b| order.less(a, b));
+
+    let result = sort(slots);
+
+    #[cfg(all(debug_assertions, feature = "check-permutation"))]
+    debug_assert!(
+        crate::util::rank_summary(slots, &|a, b| order.less(a, b)) == summary,
+        "sorting did not preserve the elements"
+    );
+
+    result
+}
+
+fn cycle_impl_unchecked<S, L, H>(
+    slots: &mut S,
+    start: usize,
+    order: &L,
+    hook: &mut H,
+) -> Result<usize, Stopped>
+where
+    S: Slots + ?Sized,
+    L: Less<S::Item>,
+    H: Hook,
+{
+    let length = slots.len();
     let mut writes = 0;
 
-    for src in 0..length - 1 {
-        let mut tmp = unsafe { ManuallyDrop::new(ptr::read(&slice[src])) };
+    for src in start..length - 1 {
+        hook.start(src);
+
+        let mut tmp = unsafe { ManuallyDrop::new(ptr::read(slots.slot(src))) };
+        let mut from = src;
+
+        if cycle(slots, src, &mut from, &mut tmp, &mut writes, order, hook).is_break() {
+            // `slots[src]` is a stale copy as soon as the first write is made,
+            // so put the element in flight there
+            if from != src {
+                mem::swap(&mut *tmp, slots.slot_mut(src));
+                writes += 1;
+            }
+
+            #[cfg(feature = "log")]
+            log::trace!("stopped at {} after {} writes", src, writes);
+
+            return Err(Stopped { src, writes });
+        }
+    }
+
+    #[cfg(feature = "log")]
+    log::trace!("sorted {} elements with {} writes", length, writes);
+
+    Ok(writes)
+}
+
+/// Follows the cycle starting at `src`, where `tmp` has been read from.
+fn cycle<S, L, H>(
+    slots: &mut S,
+    src: usize,
+    from: &mut usize,
+    tmp: &mut S::Item,
+    writes: &mut usize,
+    order: &L,
+    hook: &mut H,
+) -> ControlFlow<()>
+where
+    S: Slots + ?Sized,
+    L: Less<S::Item>,
+    H: Hook,
+{
+    let length = slots.len();
+
+    loop {
         let mut dst = src;
 
-        // count number of elements in `slice[src..]` strictly less than `tmp`
+        // count number of elements in `slots[src..]` strictly less than `tmp`
         for i in src + 1..length {
-            if is_less(&slice[i], &tmp) {
+            hook.step()?;
+            if order.less(slots.slot(i), tmp) {
                 dst += 1;
             }
         }
 
-        // tmp is in correct position, nothing to do
         if dst == src {
-            continue;
-        }
-
-        // place `tmp` after any possible duplicates
-        while util::are_equal(&*tmp, &slice[dst], is_less) {
-            dst += 1;
+            // tmp is in correct position, nothing to do
+            if *from == src {
+                return ControlFlow::Continue(());
+            }
+        } else if !H::UNIQUE || dst == *from {
+            // place `tmp` after any possible duplicates, which must be there
+            // if `tmp` belongs where it was just taken from
+            loop {
+                hook.step()?;
+                if !order.equal(tmp, slots.slot(dst)) {
+                    break;
+                }
+                dst += 1;
+                hook.skip();
+            }
         }
 
         // put `tmp` into correct position
-        mem::swap(&mut *tmp, &mut slice[dst]);
-        writes += 1;
-
-        // find correct position for whatever element was in `tmp`'s position
-        // and loop until we're back at `tmp`'s original position
-        while dst != src {
-            dst = src;
+        hook.write(src, *from, dst)?;
 
-            for i in src + 1..length {
-                if is_less(&slice[i], &tmp) {
-                    dst += 1;
-                }
+        #[cfg(feature = "log")]
+        {
+            if *from == src {
+                log::trace!("cycle started at {}", src);
             }
+            log::trace!("write from {} to {}", *from, dst);
+        }
 
-            while util::are_equal(&*tmp, &slice[dst], is_less) {
-                dst += 1;
-            }
+        mem::swap(tmp, slots.slot_mut(dst));
+        *writes += 1;
 
-            mem::swap(&mut *tmp, &mut slice[dst]);
-            writes += 1;
+        // loop until we're back at `tmp`'s original position
+        if dst == src {
+            return ControlFlow::Continue(());
         }
-    }
 
-    writes
+        // find correct position for whatever element was in `tmp`'s position
+        *from = dst;
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::cycle_sort;
+    use super::{cycle_impl_hooked, Hook, MAX_ELEMENTS_IN_FLIGHT};
+    use crate::{
+        cycle_sort, cycle_sort_assume_unique, cycle_sort_blocked, cycle_sort_bounded_stack,
+        cycle_sort_by, cycle_sort_by_val,
+    };
+
+    use core::ops::ControlFlow;
 
     extern crate std;
     use std::string::String;
@@ -156,13 +757,69 @@ mod tests {
         let mut array = [(); SIZE];
 
         for length in (0..10).chain(1000..SIZE + 1) {
-            let mut slice = &mut array[..length];
-            let writes = cycle_sort(&mut slice);
+            let slice = &mut array[..length];
+            let writes = cycle_sort(slice);
 
             assert_eq!(writes, 0);
+            assert_eq!(cycle_sort_by_val(slice, |_, _| unreachable!()), 0);
+            assert_eq!(cycle_sort_blocked(slice, 8), 0);
         }
     }
 
+    #[test]
+    fn zero_sized_elements_with_drop() {
+        use core::cmp::Ordering;
+        use core::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+        const SIZE: usize = 1100;
+
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        // a zero-sized element that counts its drops, and must never be
+        // compared, duplicated or dropped by the sort itself
+        struct Tracked;
+
+        impl Drop for Tracked {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, SeqCst);
+            }
+        }
+
+        impl PartialEq for Tracked {
+            fn eq(&self, _: &Self) -> bool {
+                unreachable!()
+            }
+        }
+
+        impl Eq for Tracked {}
+
+        impl PartialOrd for Tracked {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Tracked {
+            fn cmp(&self, _: &Self) -> Ordering {
+                unreachable!()
+            }
+        }
+
+        let mut v: Vec<Tracked> = (0..SIZE).map(|_| Tracked).collect();
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let slice = &mut v[..length];
+
+            assert_eq!(cycle_sort(slice), 0);
+            assert_eq!(cycle_sort_by(slice, &|a, b| a.cmp(b)), 0);
+            assert_eq!(DROPS.load(SeqCst), 0);
+        }
+
+        drop(v);
+
+        assert_eq!(DROPS.load(SeqCst), SIZE);
+    }
+
     #[test]
     fn basic_sort() {
         const SIZE: usize = 110;
@@ -171,11 +828,11 @@ mod tests {
         let mut rng = thread_rng();
 
         for length in (0..20).chain(100..SIZE + 1) {
-            let mut slice = &mut array[..length];
+            let slice = &mut array[..length];
 
             for _ in 0..10 {
                 rng.fill(slice);
-                cycle_sort(&mut slice);
+                let _ = cycle_sort(slice);
 
                 assert_sorted!(slice);
             }
@@ -200,7 +857,7 @@ mod tests {
 
             // shuffle and sort strings
             vec.as_mut_slice().shuffle(&mut rng);
-            cycle_sort(vec.as_mut_slice());
+            let _ = cycle_sort(vec.as_mut_slice());
 
             assert_sorted!(vec.as_slice());
         }
@@ -214,7 +871,7 @@ mod tests {
         let mut rng = thread_rng();
 
         for length in 80..SIZE {
-            let mut slice = &mut array[..length];
+            let slice = &mut array[..length];
 
             for divisor in &[11, 13, 17, 19] {
                 for _ in 0..10 {
@@ -223,7 +880,7 @@ mod tests {
                         *x %= divisor;
                     }
 
-                    cycle_sort(&mut slice);
+                    let _ = cycle_sort(slice);
 
                     assert_sorted!(slice);
                 }
@@ -231,30 +888,551 @@ mod tests {
         }
     }
 
+    #[test]
+    fn comparator_calls_with_duplicates() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for divisor in &[2, 5, 255] {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= divisor;
+                }
+                let mut b = a;
+
+                let ops = crate::cycle_sort_ops(&mut a[..length]);
+                let calls = core::cell::Cell::new(0);
+                let writes = cycle_sort_by(&mut b[..length], &|x, y| {
+                    calls.set(calls.get() + 1);
+                    x.cmp(y)
+                });
+
+                assert_eq!(a, b);
+                assert_eq!(writes, ops.writes);
+
+                // every comparison takes a single call with `fast-eq`, but
+                // checking the permutation makes calls of its own, and
+                // without it the shortest slices are sorted separately
+                if cfg!(all(debug_assertions, feature = "check-permutation")) {
+                    continue;
+                } else if cfg!(feature = "fast-eq") {
+                    assert_eq!(calls.get(), ops.comparisons);
+                } else if length > 3 {
+                    assert!(calls.get() >= ops.comparisons);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blocked() {
+        const SIZE: usize = 150;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for &divisor in &[2, 20, 255] {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= divisor;
+                }
+
+                // mostly sorted, with a few elements out of place
+                let mut nearly = a;
+                nearly[..length].sort_unstable();
+                for _ in 0..length / 10 {
+                    let (i, j) = (rng.gen_range(0, length), rng.gen_range(0, length));
+                    nearly.swap(i, j);
+                }
+
+                for input in &[a, nearly] {
+                    let mut expected = *input;
+                    let writes = cycle_sort(&mut expected[..length]);
+
+                    for &block in &[1, 3, 16, 64, 100] {
+                        let mut b = *input;
+
+                        assert_eq!(cycle_sort_blocked(&mut b[..length], block), writes);
+                        assert_eq!(b, expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "block size must be non-zero")]
+    fn blocked_zero() {
+        let _ = cycle_sort_blocked(&mut [2, 1], 0);
+    }
+
+    #[test]
+    fn bounded_stack() {
+        use core::cell::Cell;
+        use core::cmp::Ordering;
+        use std::thread;
+
+        std::thread_local! {
+            static LOWEST: Cell<usize> = const { Cell::new(usize::MAX) };
+        }
+
+        // an element recording the deepest stack address reached while
+        // comparing it
+        #[derive(PartialEq, Eq)]
+        struct Probe(u32);
+
+        impl PartialOrd for Probe {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Probe {
+            fn cmp(&self, other: &Self) -> Ordering {
+                let probe = 0_u8;
+                let probe = &probe as *const u8 as usize;
+                LOWEST.with(|lowest| lowest.set(lowest.get().min(probe)));
+                self.0.cmp(&other.0)
+            }
+        }
+
+        fn depth(length: usize) -> usize {
+            let mut v: Vec<Probe> = (0..length as u32).rev().map(Probe).collect();
+            let base = 0_u8;
+            let base = &base as *const u8 as usize;
+
+            let _ = cycle_sort_bounded_stack(&mut v);
+
+            assert!(v.windows(2).all(|w| w[0].0 <= w[1].0));
+            base - LOWEST.with(Cell::get)
+        }
+
+        // sort in threads with small stacks, which would overflow if the
+        // stack grew with the length of the slice
+        let run = |length| {
+            thread::Builder::new()
+                .stack_size(64 * 1024)
+                .spawn(move || depth(length))
+                .unwrap()
+                .join()
+                .unwrap()
+        };
+
+        let small = run(4);
+
+        for &length in &[16, 256, 2000] {
+            assert_eq!(run(length), small);
+        }
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn trace_log() {
+        use core::cell::Cell;
+        use log::{Log, Metadata, Record};
+
+        std::thread_local! {
+            static WRITES: Cell<usize> = const { Cell::new(0) };
+            static CYCLES: Cell<usize> = const { Cell::new(0) };
+            static SORTS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        // counts the messages logged on the current thread
+        struct Counter;
+
+        impl Log for Counter {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &Record) {
+                let message = std::format!("{}", record.args());
+
+                if message.starts_with("write") {
+                    WRITES.with(|w| w.set(w.get() + 1));
+                } else if message.starts_with("cycle") {
+                    CYCLES.with(|c| c.set(c.get() + 1));
+                } else if message.starts_with("sorted") {
+                    SORTS.with(|s| s.set(s.get() + 1));
+                }
+            }
+
+            fn flush(&self) {}
+        }
+
+        log::set_logger(&Counter).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let mut rng = thread_rng();
+
+        // the shortest slices are sorted separately, and traced all the same
+        for length in (0..12).chain(100..103) {
+            let mut a = [0_u8; 102];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 4;
+            }
+            let mut b = a;
+            let ops = crate::cycle_sort_ops(&mut b[..length]);
+
+            WRITES.with(|w| w.set(0));
+            CYCLES.with(|c| c.set(0));
+            SORTS.with(|s| s.set(0));
+
+            let writes = cycle_sort(&mut a[..length]);
+
+            assert_eq!(WRITES.with(Cell::get), writes);
+            assert_eq!(CYCLES.with(Cell::get), ops.cycles);
+            assert_eq!(SORTS.with(Cell::get), (length > 1) as usize);
+        }
+    }
+
+    #[test]
+    fn minimal_writes_with_duplicates() {
+        const SIZE: usize = 100;
+
+        let mut array = [0_u8; SIZE];
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let slice = &mut array[..length];
+
+            for divisor in &[1, 2, 3, 7, 19] {
+                rng.fill(&mut slice[..]);
+                for x in slice.iter_mut() {
+                    *x %= divisor;
+                }
+
+                let mut before = [0_u8; SIZE];
+                before[..length].copy_from_slice(slice);
+
+                // no position receives an element equal to the one it held
+                let writes = cycle_sort(slice);
+                let changed = slice
+                    .iter()
+                    .zip(&before[..])
+                    .filter(|(a, b)| a != b)
+                    .count();
+
+                assert_sorted!(slice);
+                assert_eq!(writes, changed);
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_heavy_writes() {
+        const SIZE: usize = 200;
+
+        let mut rng = thread_rng();
+
+        for &length in &[2, 3, 10, 50, 199, 200] {
+            for &distinct in &[2, 3, 5] {
+                for &descending in &[false, true] {
+                    let mut a = [0_u8; SIZE];
+                    rng.fill(&mut a[..length]);
+                    for x in a.iter_mut() {
+                        *x %= distinct;
+                    }
+                    if descending {
+                        a[..length].sort_by(|x, y| y.cmp(x));
+                    }
+
+                    // every position must be written to exactly when the
+                    // sorted slice has a different value there
+                    let mut expect = a;
+                    expect[..length].sort_unstable();
+                    let minimal = (0..length).filter(|&i| a[i] != expect[i]).count();
+
+                    let writes = cycle_sort(&mut a[..length]);
+
+                    assert_eq!(a, expect);
+                    assert_eq!(writes, minimal);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_writes_among_equals() {
+        // an element equal to its neighbors is never written back into
+        // the run of equal elements it already belongs to
+        assert_eq!(cycle_sort(&mut [2, 2, 1]), 2);
+        assert_eq!(cycle_sort(&mut [2, 1, 2]), 2);
+        assert_eq!(cycle_sort(&mut [1, 2, 2]), 0);
+        assert_eq!(cycle_sort(&mut [2, 2, 1, 1]), 4);
+        assert_eq!(cycle_sort(&mut [1, 2, 1, 2]), 2);
+        assert_eq!(cycle_sort(&mut [1, 1, 2, 1]), 2);
+
+        // every arrangement of up to 8 elements out of 3 distinct values
+        for length in 0..9_u32 {
+            for code in 0..3_u32.pow(length) {
+                let mut a = [0_u8; 8];
+                let mut c = code;
+                for x in a[..length as usize].iter_mut() {
+                    *x = (c % 3) as u8;
+                    c /= 3;
+                }
+
+                let slice = &mut a[..length as usize];
+                let mut expect = [0_u8; 8];
+                expect[..slice.len()].copy_from_slice(slice);
+                expect[..slice.len()].sort_unstable();
+
+                let minimal = slice
+                    .iter()
+                    .zip(&expect[..])
+                    .filter(|(x, y)| x != y)
+                    .count();
+
+                assert_eq!(cycle_sort(slice), minimal);
+                assert_sorted!(slice);
+            }
+        }
+    }
+
+    #[test]
+    fn newtypes() {
+        use core::num::Wrapping;
+
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Id(u32);
+
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u32; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 20;
+            }
+            let mut w = a.map(Wrapping);
+            let mut i = a.map(Id);
+
+            // the same comparisons and writes
+            let (mut b, mut x, mut y) = (a, w, i);
+            let ops = crate::cycle_sort_ops(&mut b[..length]);
+            assert_eq!(crate::cycle_sort_ops(&mut x[..length]), ops);
+            assert_eq!(crate::cycle_sort_ops(&mut y[..length]), ops);
+
+            let expect = cycle_sort(&mut a[..length]);
+
+            assert_eq!(cycle_sort(&mut w[..length]), expect);
+            assert_eq!(cycle_sort(&mut i[..length]), expect);
+            assert_eq!(w, a.map(Wrapping));
+            assert_eq!(i, a.map(Id));
+        }
+    }
+
+    #[test]
+    fn small_lengths() {
+        // every arrangement of 2 and 3 elements out of 3 distinct values,
+        // against the general algorithm
+        for length in 2..4_u32 {
+            for code in 0..3_u32.pow(length) {
+                let mut a = [0_u8; 3];
+                let mut c = code;
+                for x in a[..length as usize].iter_mut() {
+                    *x = (c % 3) as u8;
+                    c /= 3;
+                }
+                let mut b = a;
+
+                let writes = cycle_sort(&mut a[..length as usize]);
+                let expect = crate::cycle_sort_ops(&mut b[..length as usize]).writes;
+
+                assert_eq!(a, b);
+                assert_eq!(writes, expect);
+            }
+        }
+    }
+
     #[test]
     fn correct_writes() {
         const SIZE: usize = 25;
 
         let mut array = [0; SIZE];
 
-        for i in 0..SIZE {
-            array[i] = i;
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
         }
 
         let mut rng = thread_rng();
 
         for length in 1..SIZE + 1 {
-            let mut slice = &mut array[..length];
+            let slice = &mut array[..length];
+
+            for _ in 0..10 {
+                slice.shuffle(&mut rng);
+
+                let expect = slice.iter().enumerate().filter(|&(i, v)| i != *v).count();
+                let writes = cycle_sort(slice);
+
+                assert_sorted!(slice);
+                assert_eq!(writes, expect);
+            }
+        }
+    }
+
+    #[test]
+    fn single_rotation() {
+        use core::cell::Cell;
+
+        let mut vec: Vec<u32> = Vec::new();
+
+        for &length in &[2_usize, 3, 10, 100, 1000, 3000] {
+            for shift in &[1, length - 1] {
+                // `[shift, shift + 1, ..., length - 1, 0, ..., shift - 1]` is
+                // a single cycle of every element, so every position is
+                // written to once
+                vec.clear();
+                vec.extend((0..length).map(|x| ((x + shift) % length) as u32));
+
+                let comparisons = Cell::new(0_usize);
+                let writes = cycle_sort_by(vec.as_mut_slice(), &|a, b| {
+                    comparisons.set(comparisons.get() + 1);
+                    a.cmp(b)
+                });
+
+                assert_sorted!(vec.as_slice());
+                assert_eq!(writes, length);
+
+                // one scan per write plus one per fixed position, rather than
+                // an extra factor of `length`, but checking the permutation
+                // makes calls of its own
+                if !cfg!(all(debug_assertions, feature = "check-permutation")) {
+                    assert!(comparisons.get() <= 2 * length * length);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn assume_unique() {
+        const SIZE: usize = 40;
+
+        let mut array = [0; SIZE];
+        let mut rng = thread_rng();
+
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        for length in 0..SIZE + 1 {
+            let slice = &mut array[..length];
 
             for _ in 0..10 {
                 slice.shuffle(&mut rng);
 
                 let expect = slice.iter().enumerate().filter(|&(i, v)| i != *v).count();
-                let writes = cycle_sort(&mut slice);
+                let writes = cycle_sort_assume_unique(slice);
 
                 assert_sorted!(slice);
                 assert_eq!(writes, expect);
             }
         }
     }
+
+    #[test]
+    fn assume_unique_with_duplicates() {
+        const SIZE: usize = 40;
+
+        let mut array = [0_u8; SIZE];
+        let mut rng = thread_rng();
+
+        for divisor in &[1, 2, 5, 17] {
+            for _ in 0..10 {
+                rng.fill(&mut array[..]);
+                for x in array.iter_mut() {
+                    *x %= divisor;
+                }
+
+                // sorting must still finish, even though debug builds
+                // complain about the duplicates afterwards
+                let sorted = std::panic::catch_unwind(move || {
+                    let mut array = array;
+                    let _ = cycle_sort_assume_unique(&mut array);
+                    array
+                });
+
+                match sorted {
+                    Ok(sorted) => assert_sorted!(sorted),
+                    Err(_) if cfg!(debug_assertions) => {}
+                    Err(_) => panic!("sorting panicked in a release build"),
+                }
+            }
+        }
+    }
+
+    /// Counts the elements taken out of the slice, checking that every
+    /// write puts back the one taken out last.
+    #[derive(Default)]
+    struct InFlight {
+        /// The position the element in flight was taken from, if any.
+        taken: Option<usize>,
+        count: usize,
+        max: usize,
+    }
+
+    impl Hook for InFlight {
+        fn start(&mut self, _src: usize) {
+            assert_eq!(self.count, 0);
+        }
+
+        fn write(&mut self, src: usize, from: usize, to: usize) -> ControlFlow<()> {
+            // a cycle takes out the element at its start before writing it
+            if self.taken.is_none() {
+                assert_eq!(from, src);
+                self.taken = Some(src);
+                self.count += 1;
+            }
+            assert_eq!(self.taken, Some(from));
+
+            // writing displaces the element at `to`, unless the cycle is
+            // closed by writing into the position emptied first
+            if to == src {
+                self.taken = None;
+                self.count -= 1;
+            } else {
+                self.taken = Some(to);
+            }
+
+            self.max = self.max.max(self.count);
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn elements_in_flight() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for divisor in &[2, 5, 255] {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= divisor;
+                }
+
+                let mut hook = InFlight::default();
+                let writes = cycle_impl_hooked(&mut a[..length], 0, &|a, b| a < b, &mut hook);
+
+                assert!(writes.is_ok());
+                assert_eq!(hook.count, 0);
+                assert!(hook.max <= MAX_ELEMENTS_IN_FLIGHT);
+                if writes.ok() != Some(0) {
+                    assert_eq!(hook.max, MAX_ELEMENTS_IN_FLIGHT);
+                }
+            }
+        }
+    }
 }
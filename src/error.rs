@@ -0,0 +1,71 @@
+#![deny(missing_docs)]
+
+use core::fmt;
+
+/// The error returned by the fallible sorting functions.
+///
+/// A sort that fails leaves the slice partially sorted, but still holding
+/// every one of its elements exactly once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleSortError {
+    /// The comparison function failed to order two elements.
+    Comparator,
+    /// The slice was found not to be strictly increasing after sorting,
+    /// first at `index`.
+    Unsorted {
+        /// The first position holding an element not greater than the one
+        /// before it.
+        index: usize,
+    },
+    /// The sort was stopped after reaching the maximum number of
+    /// iterations.
+    IterationLimit,
+    /// The sort was stopped before exceeding its write budget.
+    BudgetExceeded {
+        /// The number of writes made before stopping.
+        writes: usize,
+    },
+}
+
+impl fmt::Display for CycleSortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CycleSortError::Comparator => f.write_str("comparison function failed"),
+            CycleSortError::Unsorted { index } => {
+                write!(f, "slice not strictly sorted at index {}", index)
+            }
+            CycleSortError::IterationLimit => f.write_str("iteration limit reached"),
+            CycleSortError::BudgetExceeded { writes } => {
+                write!(f, "write budget exceeded after {} writes", writes)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::string::ToString;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            CycleSortError::Comparator.to_string(),
+            "comparison function failed"
+        );
+        assert_eq!(
+            CycleSortError::Unsorted { index: 3 }.to_string(),
+            "slice not strictly sorted at index 3"
+        );
+        assert_eq!(
+            CycleSortError::IterationLimit.to_string(),
+            "iteration limit reached"
+        );
+        assert_eq!(
+            CycleSortError::BudgetExceeded { writes: 7 }.to_string(),
+            "write budget exceeded after 7 writes"
+        );
+    }
+}
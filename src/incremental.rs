@@ -0,0 +1,339 @@
+#![deny(missing_docs)]
+
+use crate::merge::{cycle_sort_append, cycle_sort_auto, sort_between};
+
+/// What is known about the order of the slice held by an
+/// [`IncrementalSorter`].
+///
+/// [`IncrementalSorter`]: struct.IncrementalSorter.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Nothing is known.
+    Unknown,
+    /// The slice was sorted, and only the elements from the first position
+    /// up to but not including the second may have changed since.
+    Edited(usize, usize),
+    /// The slice is sorted.
+    Sorted,
+}
+
+/// A slice that is sorted over and over between edits, returned by
+/// [`IncrementalSorter::new`].
+///
+/// The sorter keeps track of which positions were edited through
+/// [`get_mut`] since the slice was last sorted, so [`resort`] can find the
+/// elements around them still in their final positions in linear time,
+/// without scanning for them, and only follow the cycles through the rest.
+/// After an edit through [`as_mut_slice`], which could change any position,
+/// the slice is scanned for its unsorted parts, as [`cycle_sort_auto`] does.
+/// Either way, the writes made are the same as for [`cycle_sort`].
+///
+/// [`IncrementalSorter::new`]: struct.IncrementalSorter.html#method.new
+/// [`get_mut`]: struct.IncrementalSorter.html#method.get_mut
+/// [`resort`]: struct.IncrementalSorter.html#method.resort
+/// [`as_mut_slice`]: struct.IncrementalSorter.html#method.as_mut_slice
+/// [`cycle_sort_auto`]: fn.cycle_sort_auto.html
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::IncrementalSorter;
+/// let mut a = [5, 1, 4, 2, 3, 6, 7, 8];
+/// let mut s = IncrementalSorter::new(&mut a);
+///
+/// assert_eq!(s.resort(), 5);
+/// assert_eq!(s.as_slice(), [1, 2, 3, 4, 5, 6, 7, 8]);
+///
+/// // only the elements up to where the edited one belongs are sorted
+/// *s.get_mut(1) = 6;
+///
+/// assert_eq!(s.resort(), 4);
+/// assert_eq!(s.as_slice(), [1, 3, 4, 5, 6, 6, 7, 8]);
+/// assert_eq!(s.resort(), 0);
+/// ```
+#[derive(Debug)]
+pub struct IncrementalSorter<'a, T> {
+    slice: &'a mut [T],
+    state: State,
+}
+
+impl<'a, T> IncrementalSorter<'a, T>
+where
+    T: Ord,
+{
+    /// Wraps a slice to be sorted repeatedly, of which nothing is known, so
+    /// the first call to [`resort`] sorts all of it.
+    ///
+    /// [`resort`]: struct.IncrementalSorter.html#method.resort
+    #[inline]
+    pub fn new(slice: &'a mut [T]) -> Self {
+        IncrementalSorter {
+            slice,
+            state: State::Unknown,
+        }
+    }
+
+    /// Sorts the slice again and returns the number of writes made.
+    #[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+    #[inline]
+    pub fn resort(&mut self) -> usize {
+        let writes = match self.state {
+            State::Unknown => cycle_sort_auto(self.slice),
+            State::Edited(first, last) => sort_between(self.slice, first, last),
+            State::Sorted => 0,
+        };
+
+        self.state = State::Sorted;
+        writes
+    }
+
+    /// Returns a mutable reference to the element at `index`, which is
+    /// considered edited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> &mut T {
+        let element = &mut self.slice[index];
+
+        self.state = match self.state {
+            State::Unknown => State::Unknown,
+            State::Edited(first, last) => State::Edited(first.min(index), last.max(index + 1)),
+            State::Sorted => State::Edited(index, index + 1),
+        };
+
+        element
+    }
+
+    /// Returns the slice, in its current order.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.slice
+    }
+
+    /// Returns the slice, all of which is considered edited.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.state = State::Unknown;
+        self.slice
+    }
+}
+
+/// A sorted prefix of a buffer that grows by batches of elements appended
+/// after it, returned by [`AppendSorter::new`].
+///
+/// New elements are written into [`spare_mut`], after the sorted prefix,
+/// and merged into it by [`integrate`], which skips the part of the prefix
+/// already in place like [`cycle_sort_append`] does, and makes the same
+/// writes as [`cycle_sort`].
+///
+/// [`AppendSorter::new`]: struct.AppendSorter.html#method.new
+/// [`spare_mut`]: struct.AppendSorter.html#method.spare_mut
+/// [`integrate`]: struct.AppendSorter.html#method.integrate
+/// [`cycle_sort_append`]: fn.cycle_sort_append.html
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::AppendSorter;
+/// let mut a = [0; 8];
+/// let mut s = AppendSorter::new(&mut a);
+///
+/// s.spare_mut()[..3].copy_from_slice(&[5, 1, 3]);
+///
+/// assert_eq!(s.integrate(3), 3);
+/// assert_eq!(s.as_sorted(), [1, 3, 5]);
+///
+/// s.spare_mut()[..2].copy_from_slice(&[9, 2]);
+///
+/// assert_eq!(s.integrate(5), 4);
+/// assert_eq!(s.as_sorted(), [1, 2, 3, 5, 9]);
+/// ```
+#[derive(Debug)]
+pub struct AppendSorter<'a, T> {
+    slice: &'a mut [T],
+    sorted_len: usize,
+}
+
+impl<'a, T> AppendSorter<'a, T>
+where
+    T: Ord,
+{
+    /// Wraps a buffer with an empty sorted prefix.
+    #[inline]
+    pub fn new(slice: &'a mut [T]) -> Self {
+        AppendSorter {
+            slice,
+            sorted_len: 0,
+        }
+    }
+
+    /// Merges the elements from the end of the sorted prefix up to
+    /// `new_len` into it, so that it is `new_len` long, and returns the
+    /// number of writes made.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` is less than the length of the sorted prefix or
+    /// greater than the length of the buffer.
+    #[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+    #[inline]
+    pub fn integrate(&mut self, new_len: usize) -> usize {
+        assert!(
+            new_len <= self.slice.len(),
+            "new length {} out of range for slice of length {}",
+            new_len,
+            self.slice.len()
+        );
+        assert!(
+            new_len >= self.sorted_len,
+            "new length {} less than sorted length {}",
+            new_len,
+            self.sorted_len
+        );
+
+        let writes = cycle_sort_append(&mut self.slice[..new_len], self.sorted_len);
+
+        self.sorted_len = new_len;
+        writes
+    }
+
+    /// Returns the length of the sorted prefix.
+    #[inline]
+    pub fn sorted_len(&self) -> usize {
+        self.sorted_len
+    }
+
+    /// Returns the sorted prefix.
+    #[inline]
+    pub fn as_sorted(&self) -> &[T] {
+        &self.slice[..self.sorted_len]
+    }
+
+    /// Returns the rest of the buffer after the sorted prefix, for new
+    /// elements to be written into.
+    #[inline]
+    pub fn spare_mut(&mut self) -> &mut [T] {
+        &mut self.slice[self.sorted_len..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_sort;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let mut sorter = IncrementalSorter::new(&mut array[..length]);
+
+            assert_eq!(sorter.resort(), 0);
+            if length > 0 {
+                *sorter.get_mut(length / 2) = ();
+            }
+            assert_eq!(sorter.resort(), 0);
+        }
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let mut sorter = AppendSorter::new(&mut array[..length]);
+
+            assert_eq!(sorter.integrate(length / 2), 0);
+            assert_eq!(sorter.integrate(length), 0);
+            assert_eq!(sorter.sorted_len(), length);
+        }
+    }
+
+    #[test]
+    fn same_as_cycle_sort() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 1..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+            let mut b = a;
+
+            let mut sorter = IncrementalSorter::new(&mut b[..length]);
+
+            for round in 0..20 {
+                let expect = cycle_sort(&mut a[..length]);
+
+                assert_eq!(sorter.resort(), expect);
+                assert_eq!(sorter.as_slice(), &a[..length]);
+
+                // edit a few elements, or any of them
+                if round % 5 == 4 {
+                    let slice = sorter.as_mut_slice();
+                    let i = rng.gen_range(0, length);
+                    slice.swap(i, length - 1);
+                    a[i] = slice[i];
+                    a[length - 1] = slice[length - 1];
+                } else {
+                    for _ in 0..rng.gen_range(0, 3) {
+                        let i = rng.gen_range(0, length);
+                        let x = rng.gen_range(0, 16);
+                        *sorter.get_mut(i) = x;
+                        a[i] = x;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn appended_batches() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for _ in 0..20 {
+            let mut a = [0_u8; SIZE];
+            let mut b = [0_u8; SIZE];
+            rng.fill(&mut b[..]);
+            for x in b.iter_mut() {
+                *x %= 32;
+            }
+
+            let mut sorter = AppendSorter::new(&mut a);
+            let mut len = 0;
+
+            while len < SIZE {
+                let new_len = rng.gen_range(len, SIZE + 1);
+                sorter.spare_mut()[..new_len - len].copy_from_slice(&b[len..new_len]);
+
+                let mut expect = [0; SIZE];
+                expect[..len].copy_from_slice(sorter.as_sorted());
+                expect[len..new_len].copy_from_slice(&b[len..new_len]);
+                let writes = cycle_sort(&mut expect[..new_len]);
+
+                assert_eq!(sorter.integrate(new_len), writes);
+                assert_eq!(sorter.as_sorted(), &expect[..new_len]);
+                len = new_len;
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "new length 1 less than sorted length 2")]
+    fn integrate_shrunk() {
+        let mut a = [2, 1, 3];
+        let mut sorter = AppendSorter::new(&mut a);
+
+        let _ = sorter.integrate(2);
+        let _ = sorter.integrate(1);
+    }
+}
@@ -0,0 +1,271 @@
+#![deny(missing_docs)]
+
+use core::cell::{Cell, RefCell};
+use core::cmp::Ordering;
+use core::mem;
+
+use crate::cycle_sort::{cycle_impl_from, cycle_impl_hooked, Hook};
+use crate::{cycle_sort_by, util};
+
+/// Keeps track of the start of the current cycle.
+struct CycleStart<'a>(&'a Cell<usize>);
+
+impl Hook for CycleStart<'_> {
+    #[inline]
+    fn start(&mut self, src: usize) {
+        self.0.set(src);
+    }
+}
+
+/// Sorts a slice using a comparator function that is also given the
+/// elements' positions, and returns the number of writes made.
+///
+/// `compare` receives the *current* position of each element along with
+/// the element itself. Since sorting moves elements, the position an
+/// element is reported at changes as the sort goes on. The element being
+/// placed is reported at the position it was first taken from in the
+/// current cycle, which is the one position not holding an element of its
+/// own while the cycle is followed, so two elements are never reported at
+/// the same position. The comparator must still describe a total order of
+/// the elements for the result to be sorted.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_by_indexed;
+/// let mut a = [3, 1, 2];
+/// let     w = cycle_sort_by_indexed(&mut a, &|i, a, j, b| {
+///     assert!(i < 3 && j < 3);
+///     a.cmp(b)
+/// });
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_by_indexed<T, F>(slice: &mut [T], compare: &F) -> usize
+where
+    F: Fn(usize, &T, usize, &T) -> Ordering,
+{
+    // zero-sized elements don't have distinct addresses, but are never
+    // compared either
+    if mem::size_of::<T>() == 0 {
+        return 0;
+    }
+
+    let base = slice.as_ptr() as usize;
+    let length = slice.len();
+    let start = Cell::new(0);
+
+    // an element outside of the slice must be the one in flight
+    let index = |x: &T| {
+        let i = (x as *const T as usize).wrapping_sub(base) / mem::size_of::<T>();
+
+        if i < length {
+            i
+        } else {
+            start.get()
+        }
+    };
+
+    let is_less = |a: &T, b: &T| compare(index(a), a, index(b), b) == Ordering::Less;
+
+    match cycle_impl_hooked(slice, 0, &is_less, &mut CycleStart(&start)) {
+        Ok(writes) => writes,
+        Err(_) => unreachable!(),
+    }
+}
+
+/// The largest slice [`cycle_sort_memoized`] caches comparisons for.
+///
+/// [`cycle_sort_memoized`]: fn.cycle_sort_memoized.html
+const MEMO_MAX: usize = 64;
+
+/// Sorts a slice using a comparator function, comparing every pair of
+/// elements at most once, and returns the number of writes made.
+///
+/// Following a cycle counts the elements less than the one in flight over
+/// and over, comparing most pairs several times during a sort. For slices
+/// of up to 64 elements, every result is cached in a bitset on the stack,
+/// keyed by the positions the two elements started out at, so `compare`
+/// is called at most `n * (n - 1) / 2` times. This pays off when comparing
+/// is enormously expensive. The order is found on the unmodified slice
+/// first, before moving any of its elements, so a panicking `compare`
+/// leaves the slice as it was. Longer slices are sorted without a cache.
+///
+/// Sorting makes the same writes as [`cycle_sort_by`] would.
+///
+/// [`cycle_sort_by`]: fn.cycle_sort_by.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_memoized;
+/// let mut a = [5, 3, 4, 1, 2];
+/// let mut c = 0;
+/// let     w = cycle_sort_memoized(&mut a, &mut |a, b| {
+///     c += 1;
+///     a.cmp(b)
+/// });
+///
+/// assert_eq!(a, [1, 2, 3, 4, 5]);
+/// assert_eq!(w, 5);
+/// assert!(c <= 10);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_memoized<T, F>(slice: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let length = slice.len();
+    let compare = RefCell::new(compare);
+
+    if mem::size_of::<T>() == 0 {
+        return 0;
+    }
+
+    if length > MEMO_MAX {
+        return cycle_sort_by(slice, &|a, b| (*compare.borrow_mut())(a, b));
+    }
+
+    // bit `j` of `known[i]` is set once the elements starting at `i` and
+    // `j` have been compared, and bit `j` of `less[i]` tells if the one
+    // starting at `i` compared less
+    let mut known = [0_u64; MEMO_MAX];
+    let mut less = [0_u64; MEMO_MAX];
+    let known = Cell::from_mut(&mut known[..]).as_slice_of_cells();
+    let less = Cell::from_mut(&mut less[..]).as_slice_of_cells();
+
+    let data = &*slice;
+    let is_less = |&i: &usize, &j: &usize| {
+        if known[i].get() >> j & 1 == 0 {
+            let order = (*compare.borrow_mut())(&data[i], &data[j]);
+
+            known[i].set(known[i].get() | 1 << j);
+            known[j].set(known[j].get() | 1 << i);
+            less[i].set(less[i].get() | ((order == Ordering::Less) as u64) << j);
+            less[j].set(less[j].get() | ((order == Ordering::Greater) as u64) << i);
+        }
+
+        less[i].get() >> j & 1 == 1
+    };
+
+    // sort the starting positions of the elements, then move the elements
+    // accordingly
+    let mut order = [0; MEMO_MAX];
+    for (i, x) in order.iter_mut().enumerate() {
+        *x = i;
+    }
+    let order = &mut order[..length];
+
+    let writes = cycle_impl_from(order, 0, &is_less);
+
+    util::apply_order(slice, order);
+
+    writes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_sort;
+
+    extern crate std;
+    use std::panic::{self, AssertUnwindSafe};
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let writes = cycle_sort_by_indexed(&mut array[..length], &|_, _, _, _| unreachable!());
+
+            assert_eq!(writes, 0);
+            assert_eq!(
+                cycle_sort_memoized(&mut array[..length], &mut |_, _| unreachable!()),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn current_positions() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u32; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 20;
+            }
+            let mut b = a;
+
+            let expect = cycle_sort(&mut a[..length]);
+
+            let writes = cycle_sort_by_indexed(&mut b[..length], &|i, x, j, y| {
+                assert!(i < length && j < length);
+                assert_ne!(i, j);
+                x.cmp(y)
+            });
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn memoized() {
+        const SIZE: usize = 80;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u32; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 20;
+            }
+            let mut b = a;
+
+            let expect = crate::cycle_sort_by(&mut a[..length], &|x, y| x.cmp(y));
+
+            let mut calls = 0;
+            let writes = cycle_sort_memoized(&mut b[..length], &mut |x, y| {
+                calls += 1;
+                x.cmp(y)
+            });
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+
+            if length <= MEMO_MAX {
+                assert!(calls <= length * length.saturating_sub(1) / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn memoized_panic_leaves_slice() {
+        let mut a = [5, 3, 4, 1, 2];
+        let mut calls = 0;
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            cycle_sort_memoized(&mut a, &mut |x, y| {
+                calls += 1;
+                assert!(calls < 5);
+                x.cmp(y)
+            })
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(a, [5, 3, 4, 1, 2]);
+    }
+}
@@ -0,0 +1,611 @@
+#![deny(missing_docs)]
+
+use core::cmp::{Ordering, Reverse};
+use core::ops::ControlFlow;
+
+use crate::cycle_sort::{cycle_impl_from, cycle_impl_hooked, Hook};
+
+/// Sorts a slice by a fixed-size byte array key, compared
+/// lexicographically, and returns the number of writes made.
+///
+/// This is meant for sorting records by their first `K` bytes or another
+/// short byte string, without allocating: the key is extracted onto the
+/// stack for every comparison, so `key` should be cheap.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_by_array_key;
+/// // sort by the first two bytes
+/// let mut a = [*b"zap", *b"abc", *b"abb", *b"aaz"];
+/// let     w = cycle_sort_by_array_key(&mut a, &|s| [s[0], s[1]]);
+///
+/// assert_eq!(a, [*b"aaz", *b"abc", *b"abb", *b"zap"]);
+/// assert_eq!(w, 2);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_by_array_key<T, F, const K: usize>(slice: &mut [T], key: &F) -> usize
+where
+    F: Fn(&T) -> [u8; K],
+{
+    cycle_impl_from(slice, 0, &|a: &T, b: &T| key(a) < key(b))
+}
+
+/// Sorts a slice of pairs by their first elements and returns the number
+/// of writes made.
+///
+/// The second elements are carried along and never compared, so they need
+/// not be ordered. The first elements are compared in place, so unlike
+/// with [`cycle_sort_by_key`], they are never cloned.
+///
+/// [`cycle_sort_by_key`]: fn.cycle_sort_by_key.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_tuples;
+/// let mut a = [(3_u32, "three"), (1, "one"), (2, "two")];
+/// let     w = cycle_sort_tuples(&mut a);
+///
+/// assert_eq!(a, [(1, "one"), (2, "two"), (3, "three")]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_tuples<K, V>(slice: &mut [(K, V)]) -> usize
+where
+    K: Ord,
+{
+    cycle_impl_from(slice, 0, &|a: &(K, V), b: &(K, V)| a.0 < b.0)
+}
+
+/// Sorts a slice by a primary key, breaking ties by a secondary key, and
+/// returns the number of writes made.
+///
+/// This orders the elements like sorting by the key `(primary(x),
+/// secondary(x))` would, but `secondary` is only called when comparing
+/// two elements with equal primary keys, and no tuples are built.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_by_two_keys;
+/// // by length, then alphabetically
+/// let mut a = ["pear", "fig", "apple", "kiwi"];
+/// let     w = cycle_sort_by_two_keys(&mut a, &|s| s.len(), &|s| *s);
+///
+/// assert_eq!(a, ["fig", "kiwi", "pear", "apple"]);
+/// assert_eq!(w, 4);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_by_two_keys<T, U, V, F, G>(slice: &mut [T], primary: &F, secondary: &G) -> usize
+where
+    U: Ord,
+    V: Ord,
+    F: Fn(&T) -> U,
+    G: Fn(&T) -> V,
+{
+    cycle_impl_from(
+        slice,
+        0,
+        &|a: &T, b: &T| match primary(a).cmp(&primary(b)) {
+            Ordering::Equal => secondary(a) < secondary(b),
+            order => order == Ordering::Less,
+        },
+    )
+}
+
+/// Sorts a slice by bucket, and by the elements' natural ordering within
+/// each bucket, and returns the number of writes made.
+///
+/// `bucket` maps every element to a bucket index below `num_buckets`.
+/// Elements in lower buckets come first, so this is a single sort by the
+/// pair of bucket index and element. Like any other sort in this crate,
+/// it is not stable, but only elements that compare equal can be
+/// reordered.
+///
+/// # Panics
+///
+/// Debug builds panic if `bucket` returns an index not below
+/// `num_buckets`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_bucketed;
+/// // even numbers first
+/// let mut a = [5, 2, 7, 4, 1, 8];
+/// let     w = cycle_sort_bucketed(&mut a, &|x| x % 2, 2);
+///
+/// assert_eq!(a, [2, 4, 8, 1, 5, 7]);
+/// assert_eq!(w, 6);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_bucketed<T, F>(slice: &mut [T], bucket: &F, num_buckets: usize) -> usize
+where
+    T: Ord,
+    F: Fn(&T) -> usize,
+{
+    let key = |x: &T| {
+        let b = bucket(x);
+        debug_assert!(
+            b < num_buckets,
+            "bucket {} out of range for {} buckets",
+            b,
+            num_buckets
+        );
+        b
+    };
+
+    cycle_impl_from(slice, 0, &|a: &T, b: &T| (key(a), a) < (key(b), b))
+}
+
+/// Sorts a slice of indices by the elements of `data` they refer to, and
+/// returns the number of writes made to `indices`.
+///
+/// Afterwards, `data[indices[0]] <= data[indices[1]] <= ...`, while `data`
+/// itself is left untouched. The indices may refer to any elements of
+/// `data`, in any number.
+///
+/// # Panics
+///
+/// Panics if any index is out of bounds for `data`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_indices_by_data;
+/// let d = ["c", "a", "d", "b"];
+/// let mut i = [0, 2, 3];
+/// let     w = cycle_sort_indices_by_data(&d, &mut i);
+///
+/// assert_eq!(i, [3, 0, 2]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_indices_by_data<T>(data: &[T], indices: &mut [usize]) -> usize
+where
+    T: Ord,
+{
+    if let Some(&i) = indices.iter().find(|&&i| i >= data.len()) {
+        panic!("index {} out of range for data of length {}", i, data.len());
+    }
+
+    cycle_impl_from(indices, 0, &|&a: &usize, &b: &usize| data[a] < data[b])
+}
+
+/// Sorts a slice in descending order with a key extraction function that
+/// borrows the key from the element, and returns the number of writes
+/// made.
+///
+/// Since the key is borrowed, it is never cloned, and it need not be sized,
+/// so elements can be sorted by a `str` or slice they hold.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_by_ref_key_desc;
+/// let mut a = [(1, "b"), (2, "c"), (3, "a")];
+/// let     w = cycle_sort_by_ref_key_desc(&mut a, &|x| x.1);
+///
+/// assert_eq!(a, [(2, "c"), (1, "b"), (3, "a")]);
+/// assert_eq!(w, 2);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_by_ref_key_desc<T, F, U>(slice: &mut [T], key: &F) -> usize
+where
+    F: Fn(&T) -> &U,
+    U: Ord + ?Sized,
+{
+    cycle_impl_from(slice, 0, &|a: &T, b: &T| key(b) < key(a))
+}
+
+/// Sorts a slice of [`Reverse`] wrapped elements, and so in descending
+/// order of the elements they wrap, and returns the number of writes made.
+///
+/// This is the same as [`cycle_sort`], which sorts such slices as well,
+/// but compares the wrapped elements directly.
+///
+/// [`Reverse`]: https://doc.rust-lang.org/core/cmp/struct.Reverse.html
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use core::cmp::Reverse;
+/// # use cycle_sort::cycle_sort_reverse;
+/// let mut a = [Reverse(1), Reverse(3), Reverse(2)];
+/// let     w = cycle_sort_reverse(&mut a);
+///
+/// assert_eq!(a, [Reverse(3), Reverse(2), Reverse(1)]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_reverse<T>(slice: &mut [Reverse<T>]) -> usize
+where
+    T: Ord,
+{
+    cycle_impl_from(slice, 0, &|a: &Reverse<T>, b: &Reverse<T>| b.0 < a.0)
+}
+
+/// Sorts a slice in descending order and returns the number of writes
+/// made.
+///
+/// This sorts the elements as if they were wrapped in [`Reverse`], without
+/// wrapping them first and unwrapping them after.
+///
+/// [`Reverse`]: https://doc.rust-lang.org/core/cmp/struct.Reverse.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_into_reverse;
+/// let mut a = [1, 4, 1, 5, 9, 2];
+/// let     w = cycle_sort_into_reverse(&mut a);
+///
+/// assert_eq!(a, [9, 5, 4, 2, 1, 1]);
+/// assert_eq!(w, 6);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_into_reverse<T>(slice: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    cycle_impl_from(slice, 0, &|a: &T, b: &T| b < a)
+}
+
+/// Repeats every move made on the keys on the slice they belong to.
+///
+/// The keys are moved through a temporary, but the slice can only be
+/// swapped, so the position a cycle starts at holds the element belonging
+/// to the key in flight.
+pub(crate) struct Mirror<'a, T>(pub &'a mut [T]);
+
+impl<T> Hook for Mirror<'_, T> {
+    #[inline]
+    fn write(&mut self, src: usize, _from: usize, to: usize) -> ControlFlow<()> {
+        self.0.swap(src, to);
+        ControlFlow::Continue(())
+    }
+}
+
+/// Fills `keys` with the key of every element and sorts both by them.
+fn cached_key_impl<T, U, F, L>(slice: &mut [T], keys: &mut [U], key: &F, is_less: &L) -> usize
+where
+    F: Fn(&T) -> U,
+    L: Fn(&U, &U) -> bool,
+{
+    assert_eq!(
+        slice.len(),
+        keys.len(),
+        "key buffer length must equal slice length"
+    );
+
+    for (k, x) in keys.iter_mut().zip(slice.iter()) {
+        *k = key(x);
+    }
+
+    match cycle_impl_hooked(keys, 0, is_less, &mut Mirror(slice)) {
+        Ok(writes) => writes,
+        Err(_) => unreachable!(),
+    }
+}
+
+/// Sorts a slice in descending order with a key extraction function,
+/// calling it only once per element, and returns the number of writes
+/// made.
+///
+/// The keys are stored in the caller-provided buffer `keys`, which ends up
+/// holding the key of every element in sorted order, so `keys[i]` is the
+/// key of `slice[i]` and can be used without computing it again. The
+/// number of writes is the number of positions whose element changed,
+/// though moving the elements alongside their keys takes two writes per
+/// moved element.
+///
+/// # Panics
+///
+/// Panics if `keys` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_by_cached_key_desc;
+/// // sort by length, longest first
+/// let mut a = ["fig", "banana", "kiwi"];
+/// let mut k = [0; 3];
+/// let     w = cycle_sort_by_cached_key_desc(&mut a, &mut k, &|s| s.len());
+///
+/// assert_eq!(a, ["banana", "kiwi", "fig"]);
+/// assert_eq!(k, [6, 4, 3]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_by_cached_key_desc<T, F, U>(slice: &mut [T], keys: &mut [U], key: &F) -> usize
+where
+    F: Fn(&T) -> U,
+    U: Ord,
+{
+    cached_key_impl(slice, keys, key, &|a: &U, b: &U| b.lt(a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cycle_sort_by, cycle_sort_by_key};
+
+    use core::cell::Cell;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn array_key() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u64; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 1 << 20;
+            }
+            let mut b = a;
+
+            // big endian bytes compare like the numbers themselves
+            let expect = cycle_sort_by_key(&mut a[..length], &|x| *x);
+            let writes = cycle_sort_by_array_key(&mut b[..length], &|x| x.to_be_bytes());
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [((), ()); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            assert_eq!(cycle_sort_tuples(&mut array[..length]), 0);
+            assert_eq!(
+                cycle_sort_by_two_keys(&mut array[..length], &|_| unreachable!(), &|_| ()),
+                0
+            );
+            assert_eq!(
+                cycle_sort_by_ref_key_desc(&mut array[..length], &|x| &x.0),
+                0
+            );
+            assert_eq!(
+                cycle_sort_bucketed(&mut array[..length], &|_| unreachable!(), 1),
+                0
+            );
+            assert_eq!(cycle_sort_into_reverse(&mut array[..length]), 0);
+            assert_eq!(cycle_sort_reverse(&mut [Reverse(()); SIZE][..length]), 0);
+        }
+    }
+
+    #[test]
+    fn reversed() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+            let mut b = a;
+            let mut c = [Reverse(0); SIZE];
+            for (y, &x) in c.iter_mut().zip(&a) {
+                *y = Reverse(x);
+            }
+            let mut d = c;
+
+            let expect = cycle_sort_by(&mut a[..length], &|a, b| b.cmp(a));
+
+            assert_eq!(cycle_sort_into_reverse(&mut b[..length]), expect);
+            assert_eq!(crate::cycle_sort(&mut c[..length]), expect);
+            assert_eq!(cycle_sort_reverse(&mut d[..length]), expect);
+            assert_eq!(a, b);
+            assert!(c
+                .iter()
+                .zip(&d)
+                .zip(&a)
+                .all(|((c, d), &a)| c.0 == a && d.0 == a));
+        }
+    }
+
+    #[test]
+    fn bucketed() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            let mut b = a;
+
+            let expect = cycle_sort_by_key(&mut a[..length], &|x| (x % 7, *x));
+            let writes = cycle_sort_bucketed(&mut b[..length], &|x| usize::from(x % 7), 7);
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic]
+    fn bucket_out_of_range() {
+        let _ = cycle_sort_bucketed(&mut [1, 2, 3], &|x| *x, 3);
+    }
+
+    #[test]
+    fn indices_by_data() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut data = [0_u8; SIZE];
+            rng.fill(&mut data[..]);
+            for x in data.iter_mut() {
+                *x %= 16;
+            }
+
+            let mut a = [0; SIZE];
+            for i in a[..length].iter_mut() {
+                *i = rng.gen_range(0, SIZE);
+            }
+            let mut b = a;
+
+            let expect = cycle_sort_by_key(&mut a[..length], &|&i| data[i]);
+            let writes = cycle_sort_indices_by_data(&data, &mut b[..length]);
+
+            assert_eq!(writes, expect);
+            assert!(b[..length].windows(2).all(|w| data[w[0]] <= data[w[1]]));
+
+            // the same indices, possibly reordered among equal data
+            b[..length].sort_unstable();
+            a[..length].sort_unstable();
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 out of range")]
+    fn index_out_of_range() {
+        let _ = cycle_sort_indices_by_data(&[1, 2, 3], &mut [0, 3]);
+    }
+
+    #[test]
+    fn tuples() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [(0_u8, 0_u8); SIZE];
+            for (i, x) in a[..length].iter_mut().enumerate() {
+                *x = (rng.gen_range(0, 10), i as u8);
+            }
+            let mut b = a;
+
+            let expect = cycle_sort_by_key(&mut a[..length], &|x| x.0);
+            let writes = cycle_sort_tuples(&mut b[..length]);
+
+            assert_eq!(writes, expect);
+            assert!(b[..length].windows(2).all(|w| w[0].0 <= w[1].0));
+
+            // every value was carried along with its key
+            let mut c = b;
+            c[..length].sort_by_key(|x| x.1);
+            for (i, x) in c[..length].iter().enumerate() {
+                assert_eq!(x.1, i as u8);
+            }
+        }
+    }
+
+    #[test]
+    fn two_keys() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u16; SIZE];
+            rng.fill(&mut a[..length]);
+            let mut b = a;
+
+            let (primary, secondary) = (|x: &u16| x % 4, |x: &u16| x / 4 % 8);
+
+            let expect = cycle_sort_by_key(&mut a[..length], &|x| (primary(x), secondary(x)));
+
+            // secondary keys are only needed for equal primary keys
+            let calls = Cell::new(0);
+            let writes = cycle_sort_by_two_keys(&mut b[..length], &primary, &|x| {
+                calls.set(calls.get() + 1);
+                secondary(x)
+            });
+
+            assert_eq!(writes, expect);
+            assert!(a[..length]
+                .iter()
+                .zip(&b[..length])
+                .all(|(x, y)| (primary(x), secondary(x)) == (primary(y), secondary(y))));
+
+            if a[..length]
+                .windows(2)
+                .all(|w| primary(&w[0]) != primary(&w[1]))
+            {
+                assert_eq!(calls.get(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn ref_key_desc() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [[0_u8; 3]; SIZE];
+            for x in a[..length].iter_mut() {
+                *x = [rng.gen_range(0, 3), rng.gen_range(0, 3), rng.gen()];
+            }
+            let mut b = a;
+
+            let expect = cycle_sort_by(&mut a[..length], &|x, y| y[..2].cmp(&x[..2]));
+            let writes = cycle_sort_by_ref_key_desc(&mut b[..length], &|x| &x[..2]);
+
+            assert_eq!(writes, expect);
+            assert!(b[..length].windows(2).all(|w| w[0][..2] >= w[1][..2]));
+        }
+    }
+
+    #[test]
+    fn cached_key_desc() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u32; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+            let mut b = a;
+            let mut keys = [0_u64; SIZE];
+
+            let calls = Cell::new(0);
+            let expect = cycle_sort_by(&mut a[..length], &|x, y| y.cmp(x));
+            let writes =
+                cycle_sort_by_cached_key_desc(&mut b[..length], &mut keys[..length], &|x| {
+                    calls.set(calls.get() + 1);
+                    u64::from(*x)
+                });
+
+            assert_eq!(writes, expect);
+            assert_eq!(calls.get(), length);
+            assert_eq!(a, b);
+
+            for (k, x) in keys[..length].iter().zip(&b[..length]) {
+                assert_eq!(*k, u64::from(*x));
+            }
+        }
+    }
+}
@@ -12,6 +12,59 @@
 //! may want to consider checking if sorting is necessary before
 //! actually sorting.
 //!
+//! # Writes
+//!
+//! Every position is written to at most once, and only if it doesn't
+//! already hold an element equal to the one it holds after sorting. Equal
+//! elements are treated as interchangeable, so the number of writes
+//! reported is exactly the number of positions whose value changes, which
+//! is the fewest writes any sort can make.
+//!
+//! # Zero-sized types
+//!
+//! Elements of zero-sized types, such as `()`, are indistinguishable from
+//! each other, so sorting a slice of them is always a no-op. Every function
+//! in this crate reports zero writes for such slices, regardless of their
+//! length.
+//!
+//! # Newtypes
+//!
+//! Every function here is generic, and is compiled separately for every
+//! element type it is used with. A newtype such as
+//! `core::num::Wrapping<u32>`, whose ordering is that of the type it
+//! wraps, is sorted exactly like that type, making the same comparisons
+//! and writes, and with the wrapper optimized away. No special handling is
+//! needed for `#[repr(transparent)]` newtypes, which are laid out like the
+//! wrapped type too.
+//!
+//! # Features
+//!
+//! * `alloc`: add functions that need to allocate, such as
+//!   [`cycle_sort_from_iter`]. The rest of the crate never allocates.
+//! * `check-permutation`: in debug builds, verify after every sort that no
+//!   element was lost or duplicated. This makes sorting about twice as slow
+//!   and calls the comparison function for the check as well, so it is
+//!   meant for testing only. It has no effect in release builds. Sorts
+//!   whose comparisons may fail or be cut short, [`cycle_sort_guarded`],
+//!   [`cycle_sort_partial_ord`] and [`cycle_sort_by_lenient`], are not
+//!   checked, so that the comparison function is only called by the sort.
+//! * `fast-eq`: make [`cycle_sort_by`] tell equal elements apart with a
+//!   single call to the comparison function, instead of checking that
+//!   neither element is less than the other with two calls. This roughly
+//!   halves the comparisons spent skipping duplicates, and gives the same
+//!   result for any comparison function describing a total order.
+//! * `log`: emit [`log`] trace messages whenever a cycle is started, an
+//!   element is written and a sort finishes, for diagnosing unexpected
+//!   write counts. Without it, no logging code is compiled in.
+//! * `simd`: add [`cycle_sort_simd_i32`] and [`cycle_sort_simd_u32`],
+//!   which count smaller elements with vector instructions where
+//!   available. These only rely on the compiler vectorizing plain code, so
+//!   they work on stable Rust and on every target.
+//! * `smallvec`: add [`cycle_sort_smallvec`] for sorting a
+//!   `smallvec::SmallVec` in place.
+//! * `std`: add [`cycle_sort_or_std`], which falls back to the standard
+//!   library's sort for long slices. Implies `alloc`.
+//!
 //! # Safety
 //!
 //! If the comparison function passed to [`cycle_sort_by`] or the key
@@ -19,9 +72,105 @@
 //! data being sorted is likely to end up in an invalid state.
 //!
 //! [`cycle_sort_by`]: fn.cycle_sort_by.html
+//! [`cycle_sort_by_lenient`]: fn.cycle_sort_by_lenient.html
 //! [`cycle_sort_by_key`]: fn.cycle_sort_by_key.html
+//! [`cycle_sort_from_iter`]: fn.cycle_sort_from_iter.html
+//! [`cycle_sort_guarded`]: fn.cycle_sort_guarded.html
+//! [`cycle_sort_or_std`]: fn.cycle_sort_or_std.html
+//! [`cycle_sort_partial_ord`]: fn.cycle_sort_partial_ord.html
+//! [`cycle_sort_simd_i32`]: fn.cycle_sort_simd_i32.html
+//! [`cycle_sort_simd_u32`]: fn.cycle_sort_simd_u32.html
+//! [`cycle_sort_smallvec`]: fn.cycle_sort_smallvec.html
+//! [`log`]: https://docs.rs/log/0.4/log/
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
+mod budget;
+mod check;
 mod cycle_sort;
+mod error;
+mod incremental;
+mod indexed;
+mod keys;
+mod macros;
+mod merge;
+#[cfg(test)]
+mod oracle;
+mod partition;
+mod permutation;
+mod plan;
+mod scratch;
+#[cfg(feature = "simd")]
+mod simd;
+mod special;
+mod staged;
+mod stats;
+mod swap;
 mod util;
+#[cfg(feature = "alloc")]
+mod vec;
+mod views;
 
-pub use crate::cycle_sort::{cycle_sort, cycle_sort_by, cycle_sort_by_key};
+pub use crate::budget::{
+    cycle_sort_budget, cycle_sort_budget_resume, cycle_sort_budget_start, cycle_sort_by_lenient,
+    cycle_sort_controlled, cycle_sort_guarded, cycle_sort_if_cheap, cycle_sort_move_ratio,
+    cycle_sort_partial_ord, SortProgress,
+};
+pub use crate::check::{is_sorted_unique, kendall_tau, kendall_tau_normalized, sorted_insert_pos};
+pub use crate::cycle_sort::{
+    cycle_sort, cycle_sort_assume_unique, cycle_sort_blocked, cycle_sort_bounded_stack,
+    cycle_sort_by, cycle_sort_by_key, cycle_sort_by_val, MAX_ELEMENTS_IN_FLIGHT,
+};
+pub use crate::error::CycleSortError;
+pub use crate::incremental::{AppendSorter, IncrementalSorter};
+pub use crate::indexed::{cycle_sort_by_indexed, cycle_sort_memoized};
+pub use crate::keys::{
+    cycle_sort_bucketed, cycle_sort_by_array_key, cycle_sort_by_cached_key_desc,
+    cycle_sort_by_ref_key_desc, cycle_sort_by_two_keys, cycle_sort_indices_by_data,
+    cycle_sort_into_reverse, cycle_sort_reverse, cycle_sort_tuples,
+};
+pub use crate::merge::{
+    cycle_sort_append, cycle_sort_auto, cycle_sort_detect_rotation, cycle_sort_report_presorted,
+    cycle_sort_segmented, cycle_sort_smart, cycle_sort_two_sorted,
+};
+pub use crate::partition::{
+    cycle_partition, cycle_sort_median, cycle_sort_select, k_smallest_original_indices,
+};
+pub use crate::permutation::{
+    already_placed, cheaper_direction, cycle_sort_count_swaps, cycle_sort_cycle_type,
+    cycle_sort_forward_map, cycle_sort_max_displacement, cycle_sort_parity,
+    cycle_sort_permutation_order, cycle_sort_transpositions, displaced_count, expected_writes,
+    predict_writes, reverse_in_place, should_sort, stable_sort_writes, write_cycle_notation,
+    writes_saved_vs_selection, writes_to_reverse, writes_to_transform, Direction,
+};
+pub use crate::plan::{execute_plan, permutation_sorts, plan_sort};
+pub use crate::scratch::Scratch;
+#[cfg(feature = "simd")]
+pub use crate::simd::{cycle_sort_simd_i32, cycle_sort_simd_u32};
+pub use crate::special::{
+    cycle_sort_chars, cycle_sort_compact, cycle_sort_deterministic, cycle_sort_fixed_point,
+    cycle_sort_lexico, cycle_sort_small_range, cycle_sort_with_sentinel, AsBytes, Integer,
+};
+pub use crate::staged::{cycle_sort_staged, StagedSort};
+pub use crate::stats::{
+    cycle_sort_assert_strict, cycle_sort_audit, cycle_sort_cascade_report,
+    cycle_sort_cycle_comparisons, cycle_sort_dup_shifts, cycle_sort_extremes,
+    cycle_sort_first_duplicate, cycle_sort_fixed_point_indices, cycle_sort_flight_times,
+    cycle_sort_has_duplicates, cycle_sort_moved_mask, cycle_sort_ops, cycle_sort_partition_point,
+    cycle_sort_prefix_sums, cycle_sort_rank_of, cycle_sort_read_write_counts, cycle_sort_weighted,
+    cycle_sort_with_undo_log, OpCounts,
+};
+pub use crate::swap::{cycle_sort_soa, cycle_sort_with_swap};
+#[cfg(feature = "std")]
+pub use crate::vec::cycle_sort_or_std;
+#[cfg(feature = "alloc")]
+pub use crate::vec::{
+    cycle_sort_from_iter, cycle_sort_rle, debug_check_sorted_permutation, write_count_stats,
+};
+#[cfg(feature = "smallvec")]
+pub use crate::views::cycle_sort_smallvec;
+pub use crate::views::{
+    cycle_sort_batch, cycle_sort_bitonic, cycle_sort_buckets, cycle_sort_columns,
+    cycle_sort_pinned, cycle_sort_rows, cycle_sort_where, cycle_sort_wrapped,
+};
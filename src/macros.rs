@@ -0,0 +1,65 @@
+/// Defines a non-generic function sorting slices of one concrete type.
+///
+/// Every element type sorted with the generic functions of this crate gets
+/// its own copy of the sorting code, which adds up in size-constrained
+/// builds such as firmware. Sorting through functions defined with this
+/// macro instead keeps exactly one copy per type, in a place of your
+/// choosing, that can't be inlined into every call site. This trades
+/// flexibility, and some speed, for smaller code.
+///
+/// The defined function takes `&mut [T]` and returns the number of writes
+/// made, just like [`cycle_sort`]. Attributes, such as doc comments, and a
+/// visibility may be given before the name.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate cycle_sort;
+/// cycle_sort_for! {
+///     /// Sorts sensor readings.
+///     pub sort_u16: u16
+/// }
+///
+/// # fn main() {
+/// let mut a = [3, 1, 2_u16];
+/// let     w = sort_u16(&mut a);
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(w, 3);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cycle_sort_for {
+    ($(#[$attr:meta])* $vis:vis $name:ident: $ty:ty) => {
+        $(#[$attr])*
+        #[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+        #[inline(never)]
+        $vis fn $name(slice: &mut [$ty]) -> usize {
+            $crate::cycle_sort(slice)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cycle_sort;
+
+    cycle_sort_for!(sort_i32: i32);
+    cycle_sort_for!(sort_pairs: (u8, char));
+
+    #[test]
+    fn defined_functions() {
+        let mut a = [5, -1, 3, 3, 0];
+        let mut b = a;
+
+        assert_eq!(sort_i32(&mut a), cycle_sort(&mut b));
+        assert_eq!(a, b);
+
+        let mut c = [(2, 'a'), (1, 'b'), (1, 'a')];
+
+        assert_eq!(sort_pairs(&mut c), 2);
+        assert_eq!(c, [(1, 'a'), (1, 'b'), (2, 'a')]);
+    }
+}
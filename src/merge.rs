@@ -0,0 +1,692 @@
+#![deny(missing_docs)]
+
+use core::iter;
+
+use crate::cycle_sort::cycle_impl_from;
+
+/// Sorts a slice whose first `sorted_len` elements are already sorted and
+/// returns the number of writes made.
+///
+/// The elements in `slice[sorted_len..]` are merged into the sorted prefix.
+/// Any part of the prefix that is not greater than the smallest new element
+/// is already in its final position and is skipped entirely, so appending a
+/// few large elements to a long sorted slice is cheap. The writes made are
+/// the same as for [`cycle_sort`].
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Panics
+///
+/// Panics if `sorted_len > slice.len()`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_append;
+/// let mut a = [1, 3, 5, 7, 9, 6, 2];
+/// let     w = cycle_sort_append(&mut a, 5);
+///
+/// assert_eq!(a, [1, 2, 3, 5, 6, 7, 9]);
+/// assert_eq!(w, 6);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_append<T>(slice: &mut [T], sorted_len: usize) -> usize
+where
+    T: Ord,
+{
+    assert!(
+        sorted_len <= slice.len(),
+        "sorted length {} out of range for slice of length {}",
+        sorted_len,
+        slice.len()
+    );
+
+    let (prefix, tail) = slice.split_at(sorted_len);
+
+    debug_assert!(prefix.windows(2).all(|w| w[0] <= w[1]));
+
+    let start = match tail.iter().min() {
+        Some(min) => prefix.partition_point(|x| x <= min),
+        None => return 0,
+    };
+
+    cycle_impl_from(slice, start, &|a, b| a.lt(b))
+}
+
+/// Sorts a slice made up of two sorted halves, `slice[..mid]` and
+/// `slice[mid..]`, and returns the number of writes made.
+///
+/// Since both halves are sorted, the elements of the first one not greater
+/// than the smallest of the second are in their final positions, as are
+/// the elements of the second one not less than the largest of the first.
+/// Both are found by binary search and skipped entirely, and only the `m`
+/// elements between them are sorted, making the same writes as
+/// [`cycle_sort`]. The halves don't stay sorted once elements are moved
+/// though, so where each of those belongs is still found by a linear scan,
+/// taking `O(m^2)` comparisons, and this only pays off when the halves
+/// overlap in a small part of the slice.
+///
+/// For more than two sorted runs, see [`cycle_sort_segmented`].
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+/// [`cycle_sort_segmented`]: fn.cycle_sort_segmented.html
+///
+/// # Panics
+///
+/// Panics if `mid > slice.len()`. Debug builds also panic if either half is
+/// not sorted.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_two_sorted;
+/// let mut a = [1, 2, 5, 8, 3, 6, 9, 10];
+/// let     w = cycle_sort_two_sorted(&mut a, 4);
+///
+/// assert_eq!(a, [1, 2, 3, 5, 6, 8, 9, 10]);
+/// assert_eq!(w, 4);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_two_sorted<T>(slice: &mut [T], mid: usize) -> usize
+where
+    T: Ord,
+{
+    assert!(
+        mid <= slice.len(),
+        "mid {} out of range for slice of length {}",
+        mid,
+        slice.len()
+    );
+
+    let (left, right) = slice.split_at(mid);
+
+    debug_assert!(
+        left.windows(2).all(|w| w[0] <= w[1]) && right.windows(2).all(|w| w[0] <= w[1]),
+        "halves must be sorted"
+    );
+
+    let (start, end) = match (left.last(), right.first()) {
+        (Some(max), Some(min)) if min < max => (
+            left.partition_point(|x| x <= min),
+            mid + right.partition_point(|x| x < max),
+        ),
+        _ => return 0,
+    };
+
+    cycle_impl_from(&mut slice[start..end], 0, &|a, b| a.lt(b))
+}
+
+/// The largest fraction of adjacent pairs that may be out of order for
+/// [`cycle_sort_auto`] to consider a slice nearly sorted.
+///
+/// [`cycle_sort_auto`]: fn.cycle_sort_auto.html
+const NEARLY_SORTED: usize = 8;
+
+/// Sorts a slice using the elements' natural ordering, skipping the parts
+/// already in place if it looks nearly sorted, and returns the number of
+/// writes made.
+///
+/// The slice is first scanned once for adjacent pairs that are out of
+/// order. If none are found, nothing is done. If at most one pair in eight
+/// is out of order, the prefix and suffix already in their final positions
+/// are found in linear time and only the elements between them are sorted.
+/// Otherwise the whole slice is sorted as usual. Either way, the writes
+/// made are the same as for [`cycle_sort`].
+///
+/// This automates checking whether sorting is necessary, at the cost of
+/// up to `3 * n` extra comparisons.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_auto;
+/// let mut a = [0, 1, 2, 3, 4, 5, 7, 6, 8, 9];
+/// let     w = cycle_sort_auto(&mut a);
+///
+/// assert_eq!(a, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+/// assert_eq!(w, 2);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_auto<T>(slice: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    let mut first = None;
+    let mut last = 0;
+    let mut descents = 0;
+
+    for (i, w) in slice.windows(2).enumerate() {
+        if w[1] < w[0] {
+            first = first.or(Some(i + 1));
+            last = i + 1;
+            descents += 1;
+        }
+    }
+
+    let first = match first {
+        Some(first) => first,
+        None => return 0,
+    };
+
+    if descents * NEARLY_SORTED > slice.len() {
+        return cycle_impl_from(slice, 0, &|a, b| a.lt(b));
+    }
+
+    sort_between(slice, first, last)
+}
+
+/// Sorts a slice whose elements before `first` and from `last` on are
+/// sorted, skipping those already in place, and returns the number of
+/// writes made.
+pub(crate) fn sort_between<T>(slice: &mut [T], first: usize, last: usize) -> usize
+where
+    T: Ord,
+{
+    // the sorted prefix up to the smallest element after it is in place,
+    // and so is the sorted suffix from the largest element before it
+    let start = match slice[first..].iter().min() {
+        Some(min) => slice[..first].partition_point(|x| x <= min),
+        None => first,
+    };
+    let end = match slice[..last].iter().max() {
+        Some(max) => last + slice[last..].partition_point(|x| x < max),
+        None => last,
+    };
+
+    cycle_impl_from(&mut slice[start..end], 0, &|a, b| a.lt(b))
+}
+
+/// Sorts a slice using the elements' natural ordering, and returns the
+/// number of writes made along with whether it was sorted already, as
+/// `(writes, presorted)`.
+///
+/// Whether the slice is sorted is checked in linear time first, and
+/// nothing else is done if it is, so `presorted` being `true` always means
+/// zero writes were made. Since sorting an unsorted slice always makes
+/// writes, `(0, false)` is never returned.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_report_presorted;
+/// assert_eq!(cycle_sort_report_presorted(&mut [1, 2, 2, 3]), (0, true));
+/// assert_eq!(cycle_sort_report_presorted(&mut [2, 1, 3]), (2, false));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_report_presorted<T>(slice: &mut [T]) -> (usize, bool)
+where
+    T: Ord,
+{
+    if slice.windows(2).all(|w| w[0] <= w[1]) {
+        return (0, true);
+    }
+
+    (cycle_impl_from(slice, 0, &|a, b| a.lt(b)), false)
+}
+
+/// Sorts a slice using the elements' natural ordering, rotating it instead
+/// if it is a rotation of a sorted slice, and returns the number of
+/// positions whose element changed.
+///
+/// Whether the slice is a rotated sorted sequence is checked in linear
+/// time. If it is, it is sorted with [`rotate_left`] in linear time instead
+/// of the quadratic cycle sort, which would follow a single cycle through
+/// the whole slice. Rotating moves every element though, so this trades
+/// the fewest writes for speed. The count returned is the same as for
+/// [`cycle_sort`] either way.
+///
+/// [`rotate_left`]: https://doc.rust-lang.org/std/primitive.slice.html#method.rotate_left
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_detect_rotation;
+/// let mut a = [5, 6, 7, 1, 2, 3, 4];
+/// let     w = cycle_sort_detect_rotation(&mut a);
+///
+/// assert_eq!(a, [1, 2, 3, 4, 5, 6, 7]);
+/// assert_eq!(w, 7);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_detect_rotation<T>(slice: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    let length = slice.len();
+    let mut descents = slice
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[1] < w[0])
+        .map(|(i, _)| i + 1);
+
+    let mid = match (descents.next(), descents.next()) {
+        (None, _) => return 0,
+        (Some(mid), None) if slice[length - 1] <= slice[0] => mid,
+        _ => return cycle_impl_from(slice, 0, &|a, b| a.lt(b)),
+    };
+
+    let changed = (0..length)
+        .filter(|&i| slice[i] != slice[(i + mid) % length])
+        .count();
+
+    slice.rotate_left(mid);
+
+    changed
+}
+
+/// Sorts a slice using the elements' natural ordering, reversing it
+/// instead if it is sorted in descending order, and returns the number of
+/// positions whose element changed.
+///
+/// Whether the slice is in descending order is checked in linear time. If
+/// it is, it is sorted with [`reverse`] in linear time instead of the
+/// quadratic cycle sort, which would follow a cycle of two elements for
+/// every pair. Reversing swaps equal elements too though, so this trades
+/// the fewest writes for speed. The count returned is the same as for [`cycle_sort`]
+/// either way.
+///
+/// [`reverse`]: https://doc.rust-lang.org/std/primitive.slice.html#method.reverse
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_smart;
+/// let mut a = [5, 4, 3, 2, 1];
+/// let     w = cycle_sort_smart(&mut a);
+///
+/// assert_eq!(a, [1, 2, 3, 4, 5]);
+/// assert_eq!(w, 4);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_smart<T>(slice: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    let length = slice.len();
+
+    // an all equal slice is already sorted
+    let descending =
+        length > 1 && slice[length - 1] < slice[0] && slice.windows(2).all(|w| w[1] <= w[0]);
+
+    if !descending {
+        return cycle_impl_from(slice, 0, &|a, b| a.lt(b));
+    }
+
+    let changed = (0..length)
+        .filter(|&i| slice[i] != slice[length - 1 - i])
+        .count();
+
+    slice.reverse();
+
+    changed
+}
+
+/// Sorts a slice made up of sorted segments using the elements' natural
+/// ordering, and returns the number of writes made.
+///
+/// `segment_bounds` holds the positions every segment but the first starts
+/// at, so the segments are `slice[..segment_bounds[0]]`,
+/// `slice[segment_bounds[0]..segment_bounds[1]]` and so on, up to
+/// `slice[segment_bounds[k - 1]..]`.
+///
+/// The segments are only looked at before sorting. The first and last
+/// segments starting below the end of the one before them bound the part
+/// of the slice out of order, and if there are none, the slice is sorted
+/// already. The smallest start and largest end among the segments in that
+/// part tell how much of the segments around it is in place, which two
+/// binary searches find, all in `O(k)` comparisons, plus `O(log n)` for the
+/// searches. The `m` elements left are then sorted like [`cycle_sort`]
+/// does, making the same writes in `O(m^2)` comparisons, so this is most
+/// useful when few segments overlap.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Panics
+///
+/// Panics if `segment_bounds` is not in ascending order, or holds a
+/// position greater than `slice.len()`. Debug builds also panic if a
+/// segment is not sorted.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_segmented;
+/// let mut a = [1, 2, 6, 8, 3, 7, 9];
+/// let     w = cycle_sort_segmented(&mut a, &[4]);
+///
+/// assert_eq!(a, [1, 2, 3, 6, 7, 8, 9]);
+/// assert_eq!(w, 4);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_segmented<T>(slice: &mut [T], segment_bounds: &[usize]) -> usize
+where
+    T: Ord,
+{
+    let length = slice.len();
+
+    assert!(
+        segment_bounds.windows(2).all(|w| w[0] <= w[1]),
+        "segment bounds must be in ascending order"
+    );
+    if let Some(&last) = segment_bounds.last() {
+        assert!(
+            last <= length,
+            "segment bound {} out of range for slice of length {}",
+            last,
+            length
+        );
+    }
+
+    // the non-empty segments as `(start, end)`
+    let starts = iter::once(0).chain(segment_bounds.iter().copied());
+    let ends = segment_bounds.iter().copied().chain(iter::once(length));
+    let segments = || starts.clone().zip(ends.clone()).filter(|&(s, e)| s < e);
+
+    debug_assert!(
+        segments().all(|(s, e)| slice[s..e].windows(2).all(|w| w[0] <= w[1])),
+        "segments must be sorted"
+    );
+
+    // positions of segments starting below the end of the one before
+    let mut descents = segments()
+        .zip(segments().skip(1))
+        .filter(|&((_, end), (start, _))| slice[start] < slice[end - 1])
+        .map(|(_, (start, _))| start);
+
+    let first = match descents.next() {
+        Some(first) => first,
+        None => return 0,
+    };
+    let last = descents.last().unwrap_or(first);
+
+    // the sorted prefix up to the smallest element after it is in place,
+    // and so is the sorted suffix from the largest element before it
+    let min = segments()
+        .filter(|&(s, _)| s >= first)
+        .map(|(s, _)| &slice[s])
+        .min()
+        .unwrap();
+    let max = segments()
+        .filter(|&(_, e)| e <= last)
+        .map(|(_, e)| &slice[e - 1])
+        .max()
+        .unwrap();
+
+    let start = slice[..first].partition_point(|x| x <= min);
+    let end = last + slice[last..].partition_point(|x| x < max);
+
+    cycle_impl_from(&mut slice[start..end], 0, &|a, b| a.lt(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_sort;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let slice = &mut array[..length];
+
+            assert_eq!(cycle_sort_append(slice, 0), 0);
+            assert_eq!(cycle_sort_append(slice, length / 2), 0);
+            assert_eq!(cycle_sort_two_sorted(slice, length / 2), 0);
+            assert_eq!(cycle_sort_auto(slice), 0);
+            assert_eq!(cycle_sort_detect_rotation(slice), 0);
+            assert_eq!(cycle_sort_smart(slice), 0);
+            assert_eq!(cycle_sort_report_presorted(slice), (0, true));
+            assert_eq!(cycle_sort_segmented(slice, &[0, length / 2, length]), 0);
+        }
+    }
+
+    #[test]
+    fn append_matches_full_sort() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for sorted_len in 0..length + 1 {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                a[..sorted_len].sort();
+                let mut b = a;
+
+                let expect = cycle_sort(&mut a[..length]);
+                let writes = cycle_sort_append(&mut b[..length], sorted_len);
+
+                assert_eq!(a, b);
+                assert_eq!(writes, expect);
+            }
+        }
+    }
+
+    #[test]
+    fn two_sorted_matches_full_sort() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for mid in 0..length + 1 {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= 32;
+                }
+                a[..mid].sort();
+                a[mid..length].sort();
+                let mut b = a;
+
+                let expect = cycle_sort(&mut a[..length]);
+                let writes = cycle_sort_two_sorted(&mut b[..length], mid);
+
+                assert_eq!(a, b);
+                assert_eq!(writes, expect);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "mid 4 out of range for slice of length 3")]
+    fn two_sorted_out_of_range() {
+        let _ = cycle_sort_two_sorted(&mut [1, 2, 3], 4);
+    }
+
+    #[test]
+    fn auto_matches_full_sort() {
+        const SIZE: usize = 100;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for swaps in 0..6 {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= 32;
+                }
+                a[..length].sort();
+
+                // disorder a sorted slice by a few swaps
+                for _ in 0..swaps * length / 4 {
+                    let i = rng.gen_range(0, length);
+                    let j = rng.gen_range(0, length);
+                    a.swap(i, j);
+                }
+                let mut b = a;
+
+                let expect = cycle_sort(&mut a[..length]);
+                let writes = cycle_sort_auto(&mut b[..length]);
+
+                assert_eq!(a, b);
+                assert_eq!(writes, expect);
+            }
+        }
+    }
+
+    #[test]
+    fn rotations() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 1..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+
+            for (shuffled, mid) in [
+                (false, 0),
+                (false, length / 3),
+                (false, length - 1),
+                (true, 0),
+            ] {
+                let mut b = a;
+                if shuffled {
+                    b[..length].reverse();
+                } else {
+                    b[..length].sort();
+                    b[..length].rotate_right(mid);
+                }
+                let mut c = b;
+
+                let expect = cycle_sort(&mut b[..length]);
+                let writes = cycle_sort_detect_rotation(&mut c[..length]);
+
+                assert_eq!(b, c);
+                assert_eq!(writes, expect);
+            }
+        }
+    }
+
+    #[test]
+    fn segmented_matches_full_sort() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+
+            // random bounds, including empty segments
+            let mut bounds = [0; 6];
+            for b in bounds.iter_mut() {
+                *b = rng.gen_range(0, length + 1);
+            }
+            bounds.sort();
+
+            let mut prev = 0;
+            for &b in bounds.iter().chain(&[length]) {
+                a[prev..b].sort();
+                prev = b;
+            }
+            let mut b = a;
+
+            let expect = cycle_sort(&mut a[..length]);
+            let writes = cycle_sort_segmented(&mut b[..length], &bounds);
+
+            assert_eq!(a, b);
+            assert_eq!(writes, expect);
+
+            // a sorted slice in one segment
+            assert_eq!(cycle_sort_segmented(&mut b[..length], &[]), 0);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn segment_bounds_descending() {
+        let _ = cycle_sort_segmented(&mut [1, 2, 3], &[2, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn segment_bound_out_of_range() {
+        let _ = cycle_sort_segmented(&mut [1, 2, 3], &[4]);
+    }
+
+    #[test]
+    fn presorted() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+            let mut b = a;
+            let sorted = a[..length].windows(2).all(|w| w[0] <= w[1]);
+
+            let expect = cycle_sort(&mut a[..length]);
+
+            assert_eq!(
+                cycle_sort_report_presorted(&mut b[..length]),
+                (expect, sorted)
+            );
+            assert_eq!(a, b);
+            assert_eq!(cycle_sort_report_presorted(&mut b[..length]), (0, true));
+        }
+    }
+
+    #[test]
+    fn reversed() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+
+            for &descending in &[false, true] {
+                let mut b = a;
+                if descending {
+                    b[..length].sort_by(|x, y| y.cmp(x));
+                }
+                let mut c = b;
+
+                let expect = cycle_sort(&mut b[..length]);
+                let writes = cycle_sort_smart(&mut c[..length]);
+
+                assert_eq!(b, c);
+                assert_eq!(writes, expect);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn sorted_len_out_of_range() {
+        let _ = cycle_sort_append(&mut [1, 2, 3], 4);
+    }
+}
@@ -0,0 +1,116 @@
+//! A brute-force oracle for the fewest writes needed to sort a slice.
+//!
+//! Every position whose value changes must be written to at least once,
+//! which bounds the writes of any sort from below. The oracle searches all
+//! ways of sorting a small slice with a single element held outside of it,
+//! like [`cycle_sort`] does, to check that the bound can always be reached
+//! and that [`cycle_sort`] reaches it.
+//!
+//! [`cycle_sort`]: ../fn.cycle_sort.html
+
+extern crate std;
+use std::collections::{HashSet, VecDeque};
+use std::vec::Vec;
+
+use rand::{thread_rng, Rng};
+
+use crate::cycle_sort;
+
+/// The longest slice the oracle searches.
+const MAX_LEN: usize = 6;
+
+/// The number of distinct values elements may take.
+const VALUES: u8 = 4;
+
+/// A slice being sorted along with the element held outside of it.
+///
+/// A value of `0` is a hole, and `v + 1` holds the value `v`. The held
+/// element is stored last.
+type State = [u8; MAX_LEN + 1];
+
+fn encode(state: &State) -> u32 {
+    state.iter().fold(0, |key, &x| key << 3 | u32::from(x))
+}
+
+/// Returns the fewest writes that sort `slice`, found by searching every
+/// sequence of moves.
+///
+/// Picking up an element leaves a hole and is free, while putting the held
+/// element into a hole or exchanging it with an element of the slice costs
+/// one write.
+fn min_writes(slice: &[u8]) -> usize {
+    let n = slice.len();
+    let hand = MAX_LEN;
+
+    let mut start = [0; MAX_LEN + 1];
+    for (s, &x) in start.iter_mut().zip(slice) {
+        *s = x + 1;
+    }
+
+    let mut goal = start;
+    goal[..n].sort_unstable();
+
+    let mut done = HashSet::new();
+    let mut queue = VecDeque::new();
+    queue.push_back((start, 0));
+
+    // a 0-1 breadth-first search, as every move costs zero or one write
+    while let Some((state, writes)) = queue.pop_front() {
+        if state == goal {
+            return writes;
+        }
+        if !done.insert(encode(&state)) {
+            continue;
+        }
+
+        for i in 0..n {
+            let mut next = state;
+
+            match (state[hand], state[i]) {
+                (0, 0) => continue,
+                (0, _) => {
+                    next.swap(hand, i);
+                    queue.push_front((next, writes));
+                }
+                (_, _) => {
+                    next.swap(hand, i);
+                    queue.push_back((next, writes + 1));
+                }
+            }
+        }
+    }
+
+    unreachable!("every slice can be sorted")
+}
+
+#[test]
+fn oracle() {
+    assert_eq!(min_writes(&[]), 0);
+    assert_eq!(min_writes(&[0, 1, 2]), 0);
+    assert_eq!(min_writes(&[1, 0]), 2);
+    assert_eq!(min_writes(&[1, 2, 0]), 3);
+    assert_eq!(min_writes(&[1, 0, 3, 2]), 4);
+    assert_eq!(min_writes(&[1, 1, 0, 0]), 4);
+    assert_eq!(min_writes(&[0, 1, 0, 1]), 2);
+}
+
+#[test]
+fn cycle_sort_writes_are_minimal() {
+    let mut rng = thread_rng();
+
+    for length in 0..MAX_LEN + 1 {
+        for _ in 0..50 {
+            let mut a: Vec<u8> = (0..length).map(|_| rng.gen_range(0, VALUES)).collect();
+
+            let changed = {
+                let mut sorted = a.clone();
+                sorted.sort_unstable();
+                a.iter().zip(&sorted).filter(|(x, y)| x != y).count()
+            };
+            let expect = min_writes(&a);
+
+            assert_eq!(expect, changed);
+            assert_eq!(cycle_sort(&mut a), expect);
+        }
+    }
+}
@@ -0,0 +1,358 @@
+#![deny(missing_docs)]
+
+use core::mem;
+
+use crate::permutation;
+
+/// Partitions a slice around a pivot, making the fewest writes, and
+/// returns the number of writes made along with the partition boundary,
+/// as `(writes, boundary)`.
+///
+/// Afterwards, every element in `slice[..boundary]` is less than `pivot`
+/// and every element in `slice[boundary..]` is not. Only elements on the
+/// wrong side of the boundary are moved, each by being exchanged with one
+/// on the wrong side of the other part, so every position is written to at
+/// most once. Neither part is sorted.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_partition;
+/// let mut a = [1, 7, 2, 8, 3, 9, 4];
+/// let     r = cycle_partition(&mut a, &5);
+///
+/// assert_eq!(a, [1, 3, 2, 4, 7, 9, 8]);
+/// assert_eq!(r, (4, 4));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_partition<T>(slice: &mut [T], pivot: &T) -> (usize, usize)
+where
+    T: Ord,
+{
+    let boundary = slice.iter().filter(|x| **x < *pivot).count();
+    let (left, right) = slice.split_at_mut(boundary);
+
+    // the elements on the wrong side are equally many on both sides
+    let large = left.iter_mut().filter(|x| **x >= *pivot);
+    let small = right.iter_mut().filter(|x| **x < *pivot);
+
+    let writes = large.zip(small).fold(0, |writes, (a, b)| {
+        mem::swap(a, b);
+        writes + 2
+    });
+
+    (writes, boundary)
+}
+
+/// Moves the element that would end up at position `k` after sorting
+/// there, and returns the number of writes made.
+///
+/// Unlike sorting, at most one other element is moved, which is exchanged
+/// with the one belonging at `k`. So this makes at most two writes, and
+/// none if `slice[k]` is already in its place. The rest of the slice is
+/// neither partitioned nor sorted. Finding the element takes `O(n^2)`
+/// comparisons in the worst case.
+///
+/// # Panics
+///
+/// Panics if `k >= slice.len()`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_select;
+/// let mut a = [5, 1, 4, 2, 3];
+/// let     w = cycle_sort_select(&mut a, 1);
+///
+/// assert_eq!(a, [5, 2, 4, 1, 3]);
+/// assert_eq!(w, 2);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_select<T>(slice: &mut [T], k: usize) -> usize
+where
+    T: Ord,
+{
+    assert!(
+        k < slice.len(),
+        "index {} out of range for slice of length {}",
+        k,
+        slice.len()
+    );
+
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    if permutation::belongs_at(slice, k, k, &is_less) {
+        return 0;
+    }
+
+    let i = (0..slice.len())
+        .find(|&i| permutation::belongs_at(slice, i, k, &is_less))
+        .unwrap();
+
+    slice.swap(i, k);
+
+    2
+}
+
+/// Moves the lower median of a slice to its middle, and returns the number
+/// of writes made along with a clone of the median, as `(writes, median)`.
+///
+/// The lower median is the element at position `(n - 1) / 2` after sorting,
+/// which is the middle one for odd `n` and the smaller of the two middle
+/// ones for even `n`. It is moved there with [`cycle_sort_select`], so at
+/// most two writes are made.
+///
+/// [`cycle_sort_select`]: fn.cycle_sort_select.html
+///
+/// # Panics
+///
+/// Panics if the slice is empty, since it has no median.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_median;
+/// let mut a = [4, 1, 3, 2];
+/// let     m = cycle_sort_median(&mut a);
+///
+/// assert_eq!(a, [4, 2, 3, 1]);
+/// assert_eq!(m, (2, 2));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_median<T>(slice: &mut [T]) -> (usize, T)
+where
+    T: Ord + Clone,
+{
+    assert!(
+        !slice.is_empty(),
+        "cannot take the median of an empty slice"
+    );
+
+    let k = (slice.len() - 1) / 2;
+    let writes = cycle_sort_select(slice, k);
+
+    (writes, slice[k].clone())
+}
+
+/// Writes the original positions of the `k` smallest elements of a slice
+/// into `out`, without modifying the slice, and returns how many were
+/// written.
+///
+/// That is `min(k, n)` positions for a slice of `n` elements, written to
+/// the start of `out` in the order the elements would be in after sorting.
+/// Ties between equal elements are broken by position, so if only some of
+/// several equal elements are among the `k` smallest, those that come
+/// first are. Finding them takes `O(n^2)` comparisons.
+///
+/// # Panics
+///
+/// Panics if `out` is shorter than `min(k, n)`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::k_smallest_original_indices;
+/// let a = [5, 1, 4, 1, 3];
+/// let mut o = [0; 3];
+///
+/// assert_eq!(k_smallest_original_indices(&a, 3, &mut o), 3);
+/// assert_eq!(o, [1, 3, 4]);
+/// ```
+#[inline]
+pub fn k_smallest_original_indices<T>(slice: &[T], k: usize, out: &mut [usize]) -> usize
+where
+    T: Ord,
+{
+    let count = k.min(slice.len());
+
+    assert!(
+        out.len() >= count,
+        "output buffer of length {} too short for {} indices",
+        out.len(),
+        count
+    );
+
+    for (i, x) in slice.iter().enumerate() {
+        // the position of `x` after a stable sort
+        let rank = slice
+            .iter()
+            .enumerate()
+            .filter(|&(j, y)| y < x || (j < i && y == x))
+            .count();
+
+        if rank < count {
+            out[rank] = i;
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            assert_eq!(cycle_partition(&mut array[..length], &()), (0, 0));
+
+            let mut out = [0; SIZE];
+
+            assert_eq!(
+                k_smallest_original_indices(&array[..length], length / 2, &mut out),
+                length / 2
+            );
+            assert!((0..length / 2).all(|i| out[i] == i));
+
+            if length > 0 {
+                assert_eq!(cycle_sort_select(&mut array[..length], length - 1), 0);
+                assert_eq!(cycle_sort_median(&mut array[..length]), (0, ()));
+            }
+        }
+    }
+
+    #[test]
+    fn partitioned() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+
+            for pivot in 0..11 {
+                let mut b = a;
+                let (writes, boundary) = cycle_partition(&mut b[..length], &pivot);
+
+                assert!(b[..boundary].iter().all(|&x| x < pivot));
+                assert!(b[boundary..length].iter().all(|&x| x >= pivot));
+                assert_eq!(a[..length].iter().filter(|&&x| x < pivot).count(), boundary);
+
+                // only elements on the wrong side were written to
+                let changed = a.iter().zip(&b).filter(|(x, y)| x != y).count();
+                let wrong = a[..boundary].iter().filter(|&&x| x >= pivot).count()
+                    + a[boundary..length].iter().filter(|&&x| x < pivot).count();
+
+                assert_eq!(writes, wrong);
+                assert_eq!(changed, writes);
+
+                let mut sorted_a = a;
+                let mut sorted_b = b;
+                sorted_a.sort();
+                sorted_b.sort();
+                assert_eq!(sorted_a, sorted_b);
+            }
+        }
+    }
+
+    #[test]
+    fn all_equal() {
+        let mut a = [3; 10];
+
+        assert_eq!(cycle_partition(&mut a, &3), (0, 0));
+        assert_eq!(cycle_partition(&mut a, &4), (0, 10));
+        assert_eq!(cycle_partition(&mut a, &2), (0, 0));
+    }
+
+    #[test]
+    fn already_partitioned() {
+        let mut a = [2, 0, 1, 5, 9, 7];
+
+        assert_eq!(cycle_partition(&mut a, &5), (0, 3));
+        assert_eq!(cycle_partition(&mut a, &3), (0, 3));
+        assert_eq!(cycle_partition(&mut a, &0), (0, 0));
+        assert_eq!(cycle_partition(&mut a, &10), (0, 6));
+        assert_eq!(a, [2, 0, 1, 5, 9, 7]);
+    }
+
+    #[test]
+    fn select() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 1..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+
+            let mut sorted = a;
+            sorted[..length].sort();
+
+            for k in 0..length {
+                let mut b = a;
+                let writes = cycle_sort_select(&mut b[..length], k);
+                let changed = a.iter().zip(&b).filter(|(x, y)| x != y).count();
+
+                assert_eq!(b[k], sorted[k]);
+                assert_eq!(writes, changed);
+                assert_eq!(writes == 0, a[k] == sorted[k]);
+            }
+
+            let mut c = a;
+            let (writes, median) = cycle_sort_median(&mut c[..length]);
+
+            assert!(writes <= 2);
+            assert_eq!(median, sorted[(length - 1) / 2]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn median_of_empty() {
+        let _ = cycle_sort_median::<u8>(&mut []);
+    }
+
+    #[test]
+    fn k_smallest() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+
+            // the positions in stable sorted order
+            let mut order = [0; SIZE];
+            for (i, x) in order.iter_mut().enumerate() {
+                *x = i;
+            }
+            order[..length].sort_by_key(|&i| a[i]);
+
+            for k in 0..length + 2 {
+                let mut out = [usize::MAX; SIZE + 2];
+                let count = k_smallest_original_indices(&a[..length], k, &mut out);
+
+                assert_eq!(count, k.min(length));
+                assert_eq!(out[..count], order[..count]);
+                assert!(out[count..].iter().all(|&i| i == usize::MAX));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "output buffer of length 1 too short for 2 indices")]
+    fn k_smallest_short() {
+        let _ = k_smallest_original_indices(&[3, 1, 2], 2, &mut [0]);
+    }
+}
@@ -0,0 +1,1496 @@
+#![deny(missing_docs)]
+
+use core::fmt;
+use core::mem;
+
+use crate::util;
+
+/// Returns the range of positions `x` ends up among after sorting `slice`,
+/// which are the ones taken up by elements equal to it.
+fn bounds<T, F>(slice: &[T], x: &T, is_less: &F) -> (usize, usize)
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut lo = 0;
+    let mut hi = 0;
+
+    for y in slice {
+        if is_less(y, x) {
+            lo += 1;
+        } else if !is_less(x, y) {
+            hi += 1;
+        }
+    }
+
+    (lo, lo + hi)
+}
+
+/// Returns whether the element at `slice[i]` is already in its final
+/// position, so that sorting does not write to `slice[i]`.
+pub(crate) fn in_place<T, F>(slice: &[T], i: usize, is_less: &F) -> bool
+where
+    F: Fn(&T, &T) -> bool,
+{
+    belongs_at(slice, i, i, is_less)
+}
+
+/// Returns whether the element at `slice[i]`, or one equal to it, ends up
+/// at position `k` after sorting.
+pub(crate) fn belongs_at<T, F>(slice: &[T], i: usize, k: usize, is_less: &F) -> bool
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let (lo, hi) = bounds(slice, &slice[i], is_less);
+
+    lo <= k && k < hi
+}
+
+/// Returns the position the element at `slice[i]` is moved to by sorting.
+///
+/// Elements that are already among their equals' final positions stay
+/// where they are, and the remaining equal elements fill the rest of those
+/// positions in order, so that the permutation makes the fewest writes.
+pub(crate) fn destination<T, F>(slice: &[T], i: usize, is_less: &F) -> usize
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let x = &slice[i];
+    let (lo, hi) = bounds(slice, x, is_less);
+
+    if lo <= i && i < hi {
+        return i;
+    }
+
+    // count equal elements before `i` that are out of place too
+    let k = slice[..i]
+        .iter()
+        .enumerate()
+        .filter(|&(j, y)| (j < lo || j >= hi) && util::are_equal(x, y, is_less))
+        .count();
+
+    // take the k:th position in `lo..hi` not holding an equal element
+    (lo..hi)
+        .filter(|&j| !util::are_equal(x, &slice[j], is_less))
+        .nth(k)
+        .unwrap()
+}
+
+/// Iterates over the cycles of the sorting permutation of a slice, yielding
+/// their smallest positions and lengths, including cycles of length 1.
+///
+/// Each cycle is visited once, starting at its smallest position. No
+/// memory is needed for that, at the cost of following a cycle partially
+/// from every position in it, making this `O(n^3)` in the worst case.
+pub(crate) struct Cycles<'a, T, F> {
+    slice: &'a [T],
+    is_less: &'a F,
+    next: usize,
+}
+
+impl<'a, T, F> Cycles<'a, T, F>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    pub fn new(slice: &'a [T], is_less: &'a F) -> Self {
+        // all elements of zero-sized types are equal and stay in place
+        let next = if mem::size_of::<T>() == 0 {
+            slice.len()
+        } else {
+            0
+        };
+
+        Cycles {
+            slice,
+            is_less,
+            next,
+        }
+    }
+}
+
+impl<T, F> Iterator for Cycles<'_, T, F>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        while self.next < self.slice.len() {
+            let start = self.next;
+            let mut length = 1;
+            let mut i = destination(self.slice, start, self.is_less);
+
+            self.next += 1;
+
+            // only report a cycle from its smallest position
+            while i > start {
+                length += 1;
+                i = destination(self.slice, i, self.is_less);
+            }
+
+            if i == start {
+                return Some((start, length));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the number of writes sorting a slice would make, without
+/// modifying it.
+///
+/// This is the number of positions not already holding an element equal to
+/// the one they hold after sorting, and equals what [`cycle_sort`] returns.
+/// It takes `O(n^2)` comparisons, just like sorting, but makes no writes.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::predict_writes;
+/// assert_eq!(predict_writes(&[1, 4, 1, 5, 9, 2]), 5);
+/// assert_eq!(predict_writes(&[1, 2, 3]), 0);
+/// ```
+#[inline]
+pub fn predict_writes<T>(slice: &[T]) -> usize
+where
+    T: Ord,
+{
+    predict_impl(slice, &|a, b| a.lt(b))
+}
+
+/// Returns the number of positions already holding an element equal to the
+/// one they hold after sorting, without modifying the slice.
+///
+/// These are exactly the positions sorting doesn't write to, so together
+/// with [`predict_writes`] they always add up to the length of the slice.
+/// Equal elements are interchangeable, so an element counts as placed if
+/// it is anywhere among the positions taken up by its equals.
+///
+/// [`predict_writes`]: fn.predict_writes.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::already_placed;
+/// assert_eq!(already_placed(&[1, 4, 1, 5, 9, 2]), 1);
+/// assert_eq!(already_placed(&[2, 1, 3, 3]), 2);
+/// ```
+#[inline]
+pub fn already_placed<T>(slice: &[T]) -> usize
+where
+    T: Ord,
+{
+    slice.len() - predict_writes(slice)
+}
+
+/// Returns the number of elements not in their final positions, without
+/// modifying the slice.
+///
+/// Every position written to while sorting receives an element from
+/// elsewhere, and every other one keeps its element, so for a slice of
+/// length `n`
+///
+/// * the displaced count is `n` minus the number of fixed points, as
+///   returned by [`already_placed`],
+/// * it equals the number of writes, as returned by [`predict_writes`],
+/// * it is the total length of all cycles of length 2 or more, and
+/// * subtracting the number of those cycles gives the fewest swaps that
+///   sort the slice, as listed by [`cycle_sort_transpositions`].
+///
+/// Equal elements are treated as interchangeable, as usual.
+///
+/// [`already_placed`]: fn.already_placed.html
+/// [`predict_writes`]: fn.predict_writes.html
+/// [`cycle_sort_transpositions`]: fn.cycle_sort_transpositions.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{already_placed, displaced_count};
+/// let a = [2, 1, 3, 5, 6, 4];
+///
+/// // two cycles: (0 1) and (3 5 4)
+/// assert_eq!(displaced_count(&a), 5);
+/// assert_eq!(displaced_count(&a) + already_placed(&a), a.len());
+/// ```
+#[inline]
+pub fn displaced_count<T>(slice: &[T]) -> usize
+where
+    T: Ord,
+{
+    predict_writes(slice)
+}
+
+/// Returns the number of writes a stable sort of a slice would need at
+/// least, without modifying it.
+///
+/// A stable sort keeps equal elements in the order they started out in, so
+/// unlike with [`predict_writes`], they aren't interchangeable: every
+/// position has to end up with one particular element, and is written to
+/// unless it holds that element already. This is never less than what
+/// [`predict_writes`] returns, and the difference is the cost of
+/// stability. It takes `O(n^2)` comparisons.
+///
+/// [`predict_writes`]: fn.predict_writes.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{predict_writes, stable_sort_writes};
+/// // the two 1s have to swap places to stay in order
+/// let a = [2, 1, 1];
+///
+/// assert_eq!(predict_writes(&a), 2);
+/// assert_eq!(stable_sort_writes(&a), 3);
+/// ```
+#[inline]
+pub fn stable_sort_writes<T>(slice: &[T]) -> usize
+where
+    T: Ord,
+{
+    if mem::size_of::<T>() == 0 {
+        return 0;
+    }
+
+    slice
+        .iter()
+        .enumerate()
+        .filter(|&(i, x)| {
+            // the number of elements less than `x`, or equal to it and
+            // coming before it
+            let rank = slice[..i].iter().filter(|&y| y <= x).count()
+                + slice[i + 1..].iter().filter(|&y| y < x).count();
+
+            rank != i
+        })
+        .count()
+}
+
+/// Returns the expected number of writes sorting a uniformly random
+/// permutation of `n` distinct elements makes.
+///
+/// Sorting writes to every position not holding its final element already,
+/// and each of the `n` positions does with probability `1 / n`, so on
+/// average `n - 1` positions are written to, for any `n > 0`. Meanwhile the
+/// expected number of cycles, counting those of length 1, is the harmonic
+/// number `H(n)`, which makes `n - H(n)` the expected number of swaps that
+/// would sort the permutation, rather than the writes. This is exact up to
+/// the precision of `f64`, and useful for budgeting average-case writes
+/// without running any sorts.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::expected_writes;
+/// assert_eq!(expected_writes(0), 0.0);
+/// assert_eq!(expected_writes(1), 0.0);
+/// assert_eq!(expected_writes(1000), 999.0);
+/// ```
+#[inline]
+pub fn expected_writes(n: usize) -> f64 {
+    n.saturating_sub(1) as f64
+}
+
+/// Returns whether sorting a slice pays off under a simple cost model,
+/// without modifying it.
+///
+/// The cost of sorting is the number of writes it would make, as returned
+/// by [`predict_writes`], times `write_cost`. The benefit is
+/// `benefit_per_sorted_query` for each of `expected_queries`. Sorting pays
+/// off if the benefit is strictly greater than the cost. Both are computed
+/// with saturating arithmetic, so they never overflow.
+///
+/// [`predict_writes`]: fn.predict_writes.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::should_sort;
+/// // 5 writes at a cost of 10 each
+/// let a = [1, 4, 1, 5, 9, 2];
+///
+/// assert!(should_sort(&a, 10, 3, 20));
+/// assert!(!should_sort(&a, 10, 3, 10));
+/// ```
+#[inline]
+pub fn should_sort<T>(
+    slice: &[T],
+    write_cost: u64,
+    benefit_per_sorted_query: u64,
+    expected_queries: u64,
+) -> bool
+where
+    T: Ord,
+{
+    let writes = predict_writes(slice) as u64;
+
+    let cost = writes.saturating_mul(write_cost);
+    let benefit = benefit_per_sorted_query.saturating_mul(expected_queries);
+
+    benefit > cost
+}
+
+pub(crate) fn predict_impl<T, F>(slice: &[T], is_less: &F) -> usize
+where
+    F: Fn(&T, &T) -> bool,
+{
+    if mem::size_of::<T>() == 0 {
+        return 0;
+    }
+
+    (0..slice.len())
+        .filter(|&i| !in_place(slice, i, is_less))
+        .count()
+}
+
+/// Returns the fewest writes needed to rearrange `from` into `to`, or
+/// `None` if one is not a permutation of the other.
+///
+/// Equal elements are treated as interchangeable, so this is the number of
+/// positions whose elements differ: each of them must be written to, and
+/// following the cycles of a mapping that leaves every other position
+/// alone writes to each of them exactly once. When `to` is `from` sorted,
+/// this equals [`predict_writes`]. Checking that the slices are
+/// permutations of each other takes `O(n^2)` comparisons.
+///
+/// [`predict_writes`]: fn.predict_writes.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::writes_to_transform;
+/// assert_eq!(writes_to_transform(&[1, 2, 3, 1], &[3, 2, 1, 1]), Some(2));
+/// assert_eq!(writes_to_transform(&[1, 2], &[1, 3]), None);
+/// ```
+#[inline]
+pub fn writes_to_transform<T>(from: &[T], to: &[T]) -> Option<usize>
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    if from.len() != to.len() {
+        return None;
+    }
+
+    // zero-sized elements are all equal
+    if mem::size_of::<T>() == 0 {
+        return Some(0);
+    }
+
+    let count = |slice: &[T], x: &T| {
+        slice
+            .iter()
+            .filter(|y| util::are_equal(x, y, &is_less))
+            .count()
+    };
+
+    // with equal lengths, every element occurring as often in both means
+    // no other elements are left over
+    if !from.iter().all(|x| count(from, x) == count(to, x)) {
+        return None;
+    }
+
+    Some(
+        from.iter()
+            .zip(to)
+            .filter(|&(x, y)| !util::are_equal(x, y, &is_less))
+            .count(),
+    )
+}
+
+/// A sorting direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Smallest element first.
+    Ascending,
+    /// Largest element first.
+    Descending,
+}
+
+/// Returns the sorting direction that takes the fewest writes, along with
+/// that number of writes, without modifying the slice.
+///
+/// Ties are resolved in favor of [`Direction::Ascending`]. This is useful
+/// when either order is acceptable, and predicts the writes for both,
+/// taking twice as many comparisons as [`predict_writes`].
+///
+/// [`Direction::Ascending`]: enum.Direction.html#variant.Ascending
+/// [`predict_writes`]: fn.predict_writes.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cheaper_direction, Direction};
+/// assert_eq!(cheaper_direction(&[5, 4, 3, 1, 2]), (Direction::Descending, 2));
+/// assert_eq!(cheaper_direction(&[1, 2, 3, 5, 4]), (Direction::Ascending, 2));
+/// ```
+#[inline]
+pub fn cheaper_direction<T>(slice: &[T]) -> (Direction, usize)
+where
+    T: Ord,
+{
+    let ascending = predict_impl(slice, &|a: &T, b: &T| a.lt(b));
+    let descending = predict_impl(slice, &|a: &T, b: &T| b.lt(a));
+
+    if descending < ascending {
+        (Direction::Descending, descending)
+    } else {
+        (Direction::Ascending, ascending)
+    }
+}
+
+/// Returns the number of writes reversing a slice of `n` elements makes.
+///
+/// Reversing swaps every element with its mirror image, so every position
+/// is written to except the middle one of an odd-length slice, which makes
+/// `n - n % 2` writes, whatever the elements are. This is the cost of
+/// [`reverse_in_place`], and helps decide whether to reverse a slice
+/// sorted in descending order rather than sort it again. For distinct
+/// elements, both take the same number of writes, but reversing takes no
+/// comparisons, while sorting can take fewer writes if there are equal
+/// elements, as those already in their final positions stay in place.
+///
+/// [`reverse_in_place`]: fn.reverse_in_place.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::writes_to_reverse;
+/// assert_eq!(writes_to_reverse(0), 0);
+/// assert_eq!(writes_to_reverse(5), 4);
+/// assert_eq!(writes_to_reverse(6), 6);
+/// ```
+#[inline]
+pub fn writes_to_reverse(n: usize) -> usize {
+    n - n % 2
+}
+
+/// Reverses a slice and returns the number of writes made.
+///
+/// This makes [`writes_to_reverse`] writes, counting every position
+/// written to, even if it is written an element equal to the one it held.
+/// Elements of zero-sized types are never written.
+///
+/// [`writes_to_reverse`]: fn.writes_to_reverse.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::reverse_in_place;
+/// let mut a = [9, 5, 4, 1, 1];
+/// let     w = reverse_in_place(&mut a);
+///
+/// assert_eq!(a, [1, 1, 4, 5, 9]);
+/// assert_eq!(w, 4);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn reverse_in_place<T>(slice: &mut [T]) -> usize {
+    if mem::size_of::<T>() == 0 {
+        return 0;
+    }
+
+    slice.reverse();
+    writes_to_reverse(slice.len())
+}
+
+/// Returns the number of writes sorting a slice would make along with the
+/// parity of the sorting permutation, without modifying the slice.
+///
+/// The parity is `true` if the permutation is odd, that is, if it is made
+/// up of an odd number of transpositions. A cycle of length `L` takes
+/// `L - 1` transpositions but `L` writes, so the parity is not simply that
+/// of the write count.
+///
+/// If the slice contains equal elements, there is more than one sorting
+/// permutation. The one used keeps equal elements already in their final
+/// positions in place and moves the remaining ones in order.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_parity;
+/// // a single transposition
+/// assert_eq!(cycle_sort_parity(&[1, 0, 2]), (2, true));
+///
+/// // a cycle of length 3
+/// assert_eq!(cycle_sort_parity(&[1, 2, 0]), (3, false));
+/// ```
+#[inline]
+pub fn cycle_sort_parity<T>(slice: &[T]) -> (usize, bool)
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    Cycles::new(slice, &is_less)
+        .map(|(_, length)| length)
+        .filter(|&length| length > 1)
+        .fold((0, false), |(writes, odd), length| {
+            (writes + length, odd ^ (length % 2 == 0))
+        })
+}
+
+/// Returns the number of writes sorting a slice would make along with the
+/// number of pairs of elements that just swap places, without modifying the
+/// slice.
+///
+/// Those pairs are the cycles of length `2` of the sorting permutation, and
+/// make up `2` of the writes each. The remaining writes belong to longer
+/// cycles, which rotate three or more elements. Equal elements are moved as
+/// described for [`cycle_sort_parity`].
+///
+/// [`cycle_sort_parity`]: fn.cycle_sort_parity.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_count_swaps;
+/// assert_eq!(cycle_sort_count_swaps(&[1, 0, 3, 2]), (4, 2));
+/// assert_eq!(cycle_sort_count_swaps(&[1, 2, 0, 4, 3]), (5, 1));
+/// ```
+#[inline]
+pub fn cycle_sort_count_swaps<T>(slice: &[T]) -> (usize, usize)
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    Cycles::new(slice, &is_less)
+        .map(|(_, length)| length)
+        .filter(|&length| length > 1)
+        .fold((0, 0), |(writes, swaps), length| {
+            (writes + length, swaps + (length == 2) as usize)
+        })
+}
+
+/// Returns the number of writes sorting a slice would make along with the
+/// farthest distance any element is moved, without modifying the slice.
+///
+/// The distance is `0` for a sorted slice and `n - 1` for a reversed one of
+/// length `n`. Equal elements are moved as described for
+/// [`cycle_sort_parity`].
+///
+/// [`cycle_sort_parity`]: fn.cycle_sort_parity.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_max_displacement;
+/// assert_eq!(cycle_sort_max_displacement(&[1, 2, 3, 4]), (0, 0));
+/// assert_eq!(cycle_sort_max_displacement(&[4, 3, 2, 1]), (4, 3));
+/// assert_eq!(cycle_sort_max_displacement(&[2, 1, 3, 5, 4]), (4, 1));
+/// ```
+#[inline]
+pub fn cycle_sort_max_displacement<T>(slice: &[T]) -> (usize, usize)
+where
+    T: Ord,
+{
+    if mem::size_of::<T>() == 0 {
+        return (0, 0);
+    }
+
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    (0..slice.len())
+        .map(|i| destination(slice, i, &is_less).abs_diff(i))
+        .filter(|&distance| distance > 0)
+        .fold((0, 0), |(writes, max), distance| {
+            (writes + 1, max.max(distance))
+        })
+}
+
+/// Returns the number of writes sorting a slice would make, along with the
+/// number of writes selection sort would make, as
+/// `(cycle_writes, selection_writes)`, without modifying the slice.
+///
+/// Selection sort swaps the element belonging at each position into it,
+/// which takes `L - 1` swaps for a cycle of length `L`, each writing to two
+/// positions. So it makes `2 * (L - 1)` writes where cycle sort makes `L`,
+/// and the same number only for cycles of length 2. With equal elements,
+/// selection sort is assumed to pick the one that saves the most writes,
+/// moving equal elements as described for [`cycle_sort_parity`]; picking
+/// another one may make even more writes.
+///
+/// [`cycle_sort_parity`]: fn.cycle_sort_parity.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::writes_saved_vs_selection;
+/// // a single cycle of length 5
+/// assert_eq!(writes_saved_vs_selection(&[4, 0, 1, 2, 3]), (5, 8));
+///
+/// // two transpositions
+/// assert_eq!(writes_saved_vs_selection(&[1, 0, 3, 2]), (4, 4));
+/// ```
+#[inline]
+pub fn writes_saved_vs_selection<T>(slice: &[T]) -> (usize, usize)
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    Cycles::new(slice, &is_less)
+        .map(|(_, length)| length)
+        .filter(|&length| length > 1)
+        .fold((0, 0), |(cycle, selection), length| {
+            (cycle + length, selection + 2 * (length - 1))
+        })
+}
+
+/// Fills `swaps` with pairs of positions that sort a slice when swapped in
+/// order, without modifying the slice, and returns the number of pairs.
+///
+/// A cycle of length `L` takes `L - 1` swaps, all swapping the cycle's
+/// smallest position with another one in it, which is the fewest possible.
+/// Equal elements are moved as described for [`cycle_sort_parity`]. If
+/// `swaps` is too short to hold all of them, only the first ones are
+/// written, but the count returned is still the total. Finding them takes
+/// `O(n^3)` comparisons in the worst case.
+///
+/// [`cycle_sort_parity`]: fn.cycle_sort_parity.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_transpositions;
+/// let mut a = [3, 1, 2];
+/// let mut s = [(0, 0); 2];
+/// let     n = cycle_sort_transpositions(&a, &mut s);
+///
+/// assert_eq!(s, [(0, 2), (0, 1)]);
+/// for &(i, j) in &s[..n] {
+///     a.swap(i, j);
+/// }
+/// assert_eq!(a, [1, 2, 3]);
+/// ```
+#[inline]
+pub fn cycle_sort_transpositions<T>(slice: &[T], swaps: &mut [(usize, usize)]) -> usize
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+    let mut count = 0;
+
+    for (start, _) in Cycles::new(slice, &is_less) {
+        // the element at `start` is swapped into place, bringing the one
+        // from its destination to `start` next
+        let mut i = destination(slice, start, &is_less);
+
+        while i != start {
+            if let Some(s) = swaps.get_mut(count) {
+                *s = (start, i);
+            }
+            count += 1;
+            i = destination(slice, i, &is_less);
+        }
+    }
+
+    count
+}
+
+/// Fills `map` with the position every element ends up at after sorting,
+/// without modifying the slice.
+///
+/// Afterwards, `map[i]` is the position the element at `slice[i]` is moved
+/// to, which is useful for updating references to elements by their
+/// positions before sorting. Equal elements are moved as described for
+/// [`cycle_sort_parity`]. Finding where each element goes takes `O(n)`
+/// comparisons, so computing the map takes `O(n^2)`, as many as sorting.
+///
+/// [`cycle_sort_parity`]: fn.cycle_sort_parity.html
+///
+/// # Panics
+///
+/// Panics if `map` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_forward_map;
+/// let mut m = [0; 4];
+/// cycle_sort_forward_map(&["d", "b", "a", "c"], &mut m);
+///
+/// assert_eq!(m, [3, 1, 0, 2]);
+/// ```
+pub fn cycle_sort_forward_map<T>(slice: &[T], map: &mut [usize])
+where
+    T: Ord,
+{
+    assert_eq!(slice.len(), map.len(), "map length must equal slice length");
+
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    // zero-sized elements all stay in place
+    let size = mem::size_of::<T>();
+
+    for (i, m) in map.iter_mut().enumerate() {
+        *m = if size == 0 {
+            i
+        } else {
+            destination(slice, i, &is_less)
+        };
+    }
+
+    debug_assert!(
+        size == 0 || (0..map.len()).all(|j| map.iter().filter(|&&m| m == j).count() == 1),
+        "positions after sorting are not a bijection"
+    );
+}
+
+/// Returns the greatest common divisor of `a` and `b`.
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+
+    a
+}
+
+/// Returns the number of writes sorting a slice would make along with the
+/// order of the sorting permutation, without modifying the slice.
+///
+/// The order is the number of times the permutation has to be applied to
+/// the slice to get it back to how it was, which is the least common
+/// multiple of its cycle lengths. It is `1` for a sorted slice, and
+/// saturates at `u64::MAX` if it would overflow. Equal elements are moved
+/// as described for [`cycle_sort_parity`].
+///
+/// [`cycle_sort_parity`]: fn.cycle_sort_parity.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_permutation_order;
+/// assert_eq!(cycle_sort_permutation_order(&[0, 1, 2]), (0, 1));
+/// assert_eq!(cycle_sort_permutation_order(&[1, 0, 3, 4, 2]), (5, 6));
+/// ```
+#[inline]
+pub fn cycle_sort_permutation_order<T>(slice: &[T]) -> (usize, u64)
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    Cycles::new(slice, &is_less)
+        .map(|(_, length)| length)
+        .filter(|&length| length > 1)
+        .fold((0, 1), |(writes, order), length| {
+            let length = length as u64;
+            // the order only grows, so it stays saturated once it is
+            let order = match order {
+                u64::MAX => order,
+                _ => (order / gcd(order, length)).saturating_mul(length),
+            };
+
+            (writes + length as usize, order)
+        })
+}
+
+/// Fills `lengths` with the lengths of the cycles of the sorting
+/// permutation of a slice in descending order, without modifying the
+/// slice, and returns the number of cycles.
+///
+/// This is the cycle type of the permutation: cycles of length 1, made up
+/// of the elements already in place, are included, so the lengths add up
+/// to the length of the slice. Equal elements are moved as described for
+/// [`cycle_sort_parity`]. If `lengths` is too short to hold all of them,
+/// only the largest ones are written, but the count returned is still the
+/// total. Finding them takes `O(n^3)` comparisons in the worst case.
+///
+/// [`cycle_sort_parity`]: fn.cycle_sort_parity.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_cycle_type;
+/// let mut l = [0; 5];
+/// let     n = cycle_sort_cycle_type(&[1, 0, 3, 4, 2], &mut l);
+///
+/// assert_eq!(n, 2);
+/// assert_eq!(l[..n], [3, 2]);
+///
+/// let n = cycle_sort_cycle_type(&[0, 2, 1, 3, 4], &mut l);
+///
+/// assert_eq!(n, 4);
+/// assert_eq!(l[..n], [2, 1, 1, 1]);
+/// ```
+#[inline]
+pub fn cycle_sort_cycle_type<T>(slice: &[T], lengths: &mut [usize]) -> usize
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    // all elements of zero-sized types are equal and stay in place
+    if mem::size_of::<T>() == 0 {
+        for l in lengths.iter_mut().take(slice.len()) {
+            *l = 1;
+        }
+        return slice.len();
+    }
+
+    let mut count = 0;
+
+    for (_, length) in Cycles::new(slice, &is_less) {
+        count += 1;
+
+        // keep the largest lengths in order, replacing the smallest one
+        // once full
+        let mut i = match count.min(lengths.len()) {
+            0 => continue,
+            kept => kept - 1,
+        };
+        if count > lengths.len() && lengths[i] >= length {
+            continue;
+        }
+
+        lengths[i] = length;
+        while i > 0 && lengths[i - 1] < lengths[i] {
+            lengths.swap(i - 1, i);
+            i -= 1;
+        }
+    }
+
+    count
+}
+
+/// Writes the sorting permutation of a slice in cycle notation, without
+/// modifying the slice.
+///
+/// Each cycle lists the positions it moves elements between, starting at
+/// its smallest one, so that `(0 3 1)` means that the element at position
+/// `0` is moved to position `3`, the one there to position `1`, and the one
+/// there back to position `0`. Cycles are written one after another in
+/// order of their smallest positions, and positions left in place are
+/// omitted. An already sorted slice is written as `()`. Equal elements
+/// are moved as described for [`cycle_sort_parity`].
+///
+/// [`cycle_sort_parity`]: fn.cycle_sort_parity.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::write_cycle_notation;
+/// let mut s = String::new();
+/// write_cycle_notation(&[3, 9, 2, 0, 1], &mut s).unwrap();
+///
+/// assert_eq!(s, "(0 3)(1 4)");
+///
+/// s.clear();
+/// write_cycle_notation(&[1, 2, 3], &mut s).unwrap();
+///
+/// assert_eq!(s, "()");
+/// ```
+pub fn write_cycle_notation<W, T>(slice: &[T], w: &mut W) -> fmt::Result
+where
+    W: fmt::Write,
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+    let mut identity = true;
+
+    for (start, length) in Cycles::new(slice, &is_less) {
+        if length == 1 {
+            continue;
+        }
+
+        identity = false;
+        w.write_char('(')?;
+        write!(w, "{}", start)?;
+
+        let mut i = destination(slice, start, &is_less);
+        while i != start {
+            write!(w, " {}", i)?;
+            i = destination(slice, i, &is_less);
+        }
+
+        w.write_char(')')?;
+    }
+
+    if identity {
+        w.write_str("()")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_sort;
+
+    extern crate std;
+    use std::string::String;
+    use std::vec::Vec;
+
+    use rand::{seq::SliceRandom, thread_rng, Rng};
+
+    fn is_less(a: &u8, b: &u8) -> bool {
+        a < b
+    }
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            assert_eq!(predict_writes(&array[..length]), 0);
+            assert_eq!(already_placed(&array[..length]), length);
+            assert_eq!(displaced_count(&array[..length]), 0);
+            assert_eq!(stable_sort_writes(&array[..length]), 0);
+            assert_eq!(
+                cheaper_direction(&array[..length]),
+                (Direction::Ascending, 0)
+            );
+            assert_eq!(cycle_sort_parity(&array[..length]), (0, false));
+            assert_eq!(reverse_in_place(&mut [(); SIZE][..length]), 0);
+            assert_eq!(cycle_sort_count_swaps(&array[..length]), (0, 0));
+            assert_eq!(cycle_sort_max_displacement(&array[..length]), (0, 0));
+            assert_eq!(cycle_sort_permutation_order(&array[..length]), (0, 1));
+            assert_eq!(cycle_sort_cycle_type(&array[..length], &mut []), length);
+            assert_eq!(writes_saved_vs_selection(&array[..length]), (0, 0));
+            assert_eq!(cycle_sort_transpositions(&array[..length], &mut []), 0);
+            assert_eq!(
+                writes_to_transform(&array[..length], &array[..length]),
+                Some(0)
+            );
+
+            let mut map = [0; SIZE];
+            cycle_sort_forward_map(&array[..length], &mut map[..length]);
+            assert!((0..length).all(|i| map[i] == i));
+
+            let mut s = String::new();
+            write_cycle_notation(&array[..length], &mut s).unwrap();
+            assert_eq!(s, "()");
+        }
+    }
+
+    #[test]
+    fn worth_sorting() {
+        let a = [3, 1, 2];
+
+        assert!(should_sort(&a, 1, 1, 4));
+        assert!(!should_sort(&a, 1, 1, 3));
+        assert!(!should_sort(&a, 1, 0, 100));
+        assert!(should_sort(&[1, 2, 3], u64::MAX, 1, 1));
+        assert!(!should_sort(&[1, 2, 3], 0, 0, 0));
+
+        // saturating on both sides
+        assert!(!should_sort(&a, u64::MAX, u64::MAX, u64::MAX));
+        assert!(should_sort(&a, u64::MAX / 4, u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn transpositions() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+
+            let mut sorted = a;
+            let _ = cycle_sort(&mut sorted[..length]);
+
+            let mut swaps = [(0, 0); SIZE];
+            let count = cycle_sort_transpositions(&a[..length], &mut swaps);
+
+            // n minus the number of cycles, counting those of length 1
+            let cycles = Cycles::new(&a[..length], &is_less).count();
+            assert_eq!(count, length - cycles);
+
+            let mut b = a;
+            for &(i, j) in &swaps[..count] {
+                assert_ne!(i, j);
+                b.swap(i, j);
+            }
+            assert_eq!(b, sorted);
+
+            // a short buffer holds the first swaps
+            let mut short = [(0, 0); 3];
+            assert_eq!(cycle_sort_transpositions(&a[..length], &mut short), count);
+            assert_eq!(short[..count.min(3)], swaps[..count.min(3)]);
+        }
+    }
+
+    #[test]
+    fn transform() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+
+            let mut sorted = a;
+            let writes = cycle_sort(&mut sorted[..length]);
+
+            assert_eq!(
+                writes_to_transform(&a[..length], &sorted[..length]),
+                Some(writes)
+            );
+
+            // any target only takes writes where the elements differ
+            let mut b = a;
+            b[..length].shuffle(&mut rng);
+            let expect = writes_to_transform(&a[..length], &b[..length]).unwrap();
+            let moved = (0..length).filter(|&i| a[i] != b[i]).count();
+
+            assert_eq!(expect, moved);
+            assert_eq!(
+                writes_to_transform(&b[..length], &a[..length]),
+                Some(expect)
+            );
+
+            if length > 0 {
+                b[0] = 8;
+                assert_eq!(writes_to_transform(&a[..length], &b[..length]), None);
+            }
+            assert_eq!(writes_to_transform(&a[..length], &a[..length + 1]), None);
+        }
+    }
+
+    #[test]
+    fn destinations_sort() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+
+            let mut sorted = a;
+            let mut seen = [false; SIZE];
+            let writes = cycle_sort(&mut sorted[..length]);
+            let mut moved = 0;
+
+            for i in 0..length {
+                let dst = destination(&a[..length], i, &is_less);
+
+                assert!(!seen[dst]);
+                assert_eq!(a[i], sorted[dst]);
+
+                seen[dst] = true;
+                if dst != i {
+                    moved += 1;
+                }
+            }
+
+            assert_eq!(moved, writes);
+            assert_eq!(predict_writes(&a[..length]), writes);
+            assert_eq!(already_placed(&a[..length]), length - moved);
+
+            let cycled: usize = Cycles::new(&a[..length], &is_less)
+                .map(|(_, length)| length)
+                .sum();
+
+            assert_eq!(cycled, length);
+            assert_eq!(cycle_sort_max_displacement(&a[..length]).0, writes);
+        }
+    }
+
+    #[test]
+    fn stable_writes() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for divisor in &[2, 5, 255] {
+                let mut a = [(0_u8, 0); SIZE];
+                for (i, x) in a[..length].iter_mut().enumerate() {
+                    *x = (rng.gen::<u8>() % divisor, i);
+                }
+                let values: Vec<u8> = a[..length].iter().map(|x| x.0).collect();
+
+                // sorting by value and original position is the stable sort
+                let expect = cycle_sort(&mut a[..length]);
+                let writes = stable_sort_writes(&values);
+
+                assert_eq!(writes, expect);
+                assert!(writes >= predict_writes(&values));
+            }
+        }
+    }
+
+    #[test]
+    fn expected_random_writes() {
+        const SIZE: usize = 10;
+        const SAMPLES: usize = 10000;
+
+        let mut array = [0; SIZE];
+        let mut rng = thread_rng();
+
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        for length in 0..SIZE + 1 {
+            let slice = &mut array[..length];
+
+            let mut total = 0;
+            for _ in 0..SAMPLES {
+                slice.shuffle(&mut rng);
+                total += predict_writes(slice);
+            }
+
+            let mean = total as f64 / SAMPLES as f64;
+
+            assert!((mean - expected_writes(length)).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn displaced() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+            let slice = &a[..length];
+
+            let displaced = displaced_count(slice);
+            let (writes, _) = cycle_sort_parity(slice);
+            let cycles = Cycles::new(slice, &is_less)
+                .filter(|&(_, length)| length > 1)
+                .count();
+
+            assert_eq!(displaced + already_placed(slice), length);
+            assert_eq!(displaced, writes);
+            assert_eq!(
+                displaced - cycles,
+                cycle_sort_transpositions(slice, &mut [])
+            );
+        }
+    }
+
+    #[test]
+    fn directions() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+
+            let mut ascending = a;
+            let mut descending = a;
+            let up = cycle_sort(&mut ascending[..length]);
+            let down = crate::cycle_sort_by(&mut descending[..length], &|a, b| b.cmp(a));
+
+            let expect = if down < up {
+                (Direction::Descending, down)
+            } else {
+                (Direction::Ascending, up)
+            };
+
+            assert_eq!(cheaper_direction(&a[..length]), expect);
+        }
+    }
+
+    #[test]
+    fn reversed() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            a[..length].sort_unstable_by(|a, b| b.cmp(a));
+
+            let mut b = a;
+            let mut c = a;
+            b[..length].sort_unstable();
+
+            assert_eq!(
+                reverse_in_place(&mut c[..length]),
+                writes_to_reverse(length)
+            );
+            assert_eq!(b, c);
+
+            // sorting never takes more writes than reversing
+            assert!(predict_writes(&a[..length]) <= writes_to_reverse(length));
+        }
+    }
+
+    #[test]
+    fn selection_writes() {
+        const SIZE: usize = 30;
+
+        let mut array = [0; SIZE];
+        let mut rng = thread_rng();
+
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        for length in 0..SIZE + 1 {
+            for _ in 0..10 {
+                let slice = &mut array[..length];
+                slice.shuffle(&mut rng);
+
+                let (writes, selection) = writes_saved_vs_selection(slice);
+
+                // count the writes an actual selection sort makes
+                let mut sorted = [0; SIZE];
+                sorted[..length].copy_from_slice(slice);
+
+                let mut expect = 0;
+                for i in 0..length {
+                    let j = (i..length).min_by_key(|&j| sorted[j]).unwrap();
+                    if j != i {
+                        sorted.swap(i, j);
+                        expect += 2;
+                    }
+                }
+
+                assert_eq!(writes, predict_writes(slice));
+                assert_eq!(selection, expect);
+                assert!(writes <= selection);
+            }
+        }
+    }
+
+    #[test]
+    fn forward_map() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 6;
+            }
+
+            let mut sorted = a;
+            let writes = cycle_sort(&mut sorted[..length]);
+            let mut map = [0; SIZE];
+            cycle_sort_forward_map(&a[..length], &mut map[..length]);
+
+            for i in 0..length {
+                assert_eq!(sorted[map[i]], a[i]);
+            }
+            assert_eq!((0..length).filter(|&i| map[i] != i).count(), writes);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn forward_map_length_mismatch() {
+        cycle_sort_forward_map(&[2, 1], &mut [0; 3]);
+    }
+
+    #[test]
+    fn parity() {
+        assert_eq!(cycle_sort_parity::<u8>(&[]), (0, false));
+        assert_eq!(cycle_sort_parity(&[0, 1, 2, 3]), (0, false));
+        assert_eq!(cycle_sort_parity(&[1, 0, 3, 2]), (4, false));
+        assert_eq!(cycle_sort_parity(&[3, 0, 1, 2]), (4, true));
+        assert_eq!(cycle_sort_parity(&[1, 2, 0, 4, 3]), (5, true));
+        assert_eq!(cycle_sort_parity(&[4, 3, 2, 1, 0]), (4, false));
+    }
+
+    #[test]
+    fn count_swaps() {
+        const SIZE: usize = 30;
+
+        let mut array = [0; SIZE];
+        let mut rng = thread_rng();
+
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        for length in 0..SIZE + 1 {
+            for _ in 0..10 {
+                let slice = &mut array[..length];
+                slice.shuffle(&mut rng);
+
+                // count the positions whose element swaps with the one it
+                // moves onto, each pair being seen twice
+                let expect = (0..length)
+                    .filter(|&i| slice[i] != i && slice[slice[i]] == i)
+                    .count();
+
+                assert_eq!(
+                    cycle_sort_count_swaps(slice),
+                    (predict_writes(slice), expect / 2)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cycle_type() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+
+            let mut expect: Vec<usize> = Cycles::new(&a[..length], &is_less)
+                .map(|(_, length)| length)
+                .collect();
+            expect.sort_unstable_by(|a, b| b.cmp(a));
+
+            let mut lengths = [0; SIZE];
+            let count = cycle_sort_cycle_type(&a[..length], &mut lengths);
+
+            assert_eq!(count, expect.len());
+            assert_eq!(lengths[..count], expect[..]);
+            assert_eq!(lengths[..count].iter().sum::<usize>(), length);
+
+            // a short buffer holds the largest lengths
+            let mut short = [0; 3];
+            let kept = count.min(short.len());
+
+            assert_eq!(cycle_sort_cycle_type(&a[..length], &mut short), count);
+            assert_eq!(short[..kept], expect[..kept]);
+        }
+    }
+
+    #[test]
+    fn permutation_order() {
+        const SIZE: usize = 12;
+
+        let mut array = [0; SIZE];
+        let mut rng = thread_rng();
+
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        for length in 0..SIZE + 1 {
+            for _ in 0..10 {
+                let slice = &mut array[..length];
+                slice.shuffle(&mut rng);
+
+                let (writes, order) = cycle_sort_permutation_order(slice);
+
+                assert_eq!(writes, predict_writes(slice));
+
+                // applying the permutation `order` times gets back to the
+                // start, and applying it fewer times doesn't
+                let mut current = [0; SIZE];
+                current[..length].copy_from_slice(slice);
+
+                for step in 1..=order {
+                    let mut next = [0; SIZE];
+                    for i in 0..length {
+                        next[slice[i]] = current[i];
+                    }
+                    current = next;
+
+                    assert_eq!(current[..length] == slice[..], step == order);
+                }
+            }
+        }
+
+        // a product of cycles of coprime lengths, whose order is huge
+        let mut a = [0_u16; 1000];
+        let mut start = 0;
+        for &length in &[
+            2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+        ] {
+            for i in 0..length {
+                a[start + i] = (start + (i + 1) % length) as u16;
+            }
+            start += length;
+        }
+        for (i, x) in a.iter_mut().enumerate().skip(start) {
+            *x = i as u16;
+        }
+
+        assert_eq!(cycle_sort_permutation_order(&a).1, u64::MAX);
+    }
+
+    #[test]
+    fn cycle_notation() {
+        let mut s = String::new();
+
+        for &(slice, expect) in &[
+            (&[][..], "()"),
+            (&[0, 1, 2], "()"),
+            (&[2, 0, 1], "(0 2 1)"),
+            (&[1, 0, 3, 2, 4], "(0 1)(2 3)"),
+            (&[4, 3, 2, 1, 0], "(0 4)(1 3)"),
+            (&[1, 1, 0, 0], "(0 2)(1 3)"),
+            (&[0, 3, 1, 3, 2], "(1 4 2)"),
+        ] {
+            s.clear();
+            write_cycle_notation(slice, &mut s).unwrap();
+            assert_eq!(s, expect);
+        }
+    }
+
+    #[test]
+    fn parity_matches_inversions() {
+        const SIZE: usize = 20;
+
+        let mut array = [0; SIZE];
+        let mut rng = thread_rng();
+
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = i;
+        }
+
+        for length in 0..SIZE + 1 {
+            let slice = &mut array[..length];
+
+            for _ in 0..10 {
+                slice.shuffle(&mut rng);
+
+                let mut inversions = 0;
+                for i in 0..length {
+                    for j in i + 1..length {
+                        if slice[i] > slice[j] {
+                            inversions += 1;
+                        }
+                    }
+                }
+
+                let (_, odd) = cycle_sort_parity(slice);
+
+                assert_eq!(odd, inversions % 2 == 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+#![deny(missing_docs)]
+
+use core::mem;
+use core::ptr;
+
+use crate::cycle_sort_forward_map;
+
+/// Computes how sorting a slice would move its elements, without modifying
+/// it, and returns the number of writes sorting would make.
+///
+/// Afterwards, `perm[i]` is the position the element at `slice[i]` is moved
+/// to, just like with [`cycle_sort_forward_map`]. Passing `perm` to
+/// [`execute_plan`] then sorts the slice, doing the same as [`cycle_sort`]
+/// would. This allows planning the sort and carrying it out separately,
+/// for example on different threads. Planning takes `O(n^2)` comparisons,
+/// like computing the map does.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+/// [`cycle_sort_forward_map`]: fn.cycle_sort_forward_map.html
+/// [`execute_plan`]: fn.execute_plan.html
+///
+/// # Panics
+///
+/// Panics if `perm` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::plan_sort;
+/// let mut p = [0; 3];
+/// let     w = plan_sort(&[3, 1, 2], &mut p);
+///
+/// assert_eq!(p, [2, 0, 1]);
+/// assert_eq!(w, 3);
+/// ```
+#[inline]
+pub fn plan_sort<T>(slice: &[T], perm: &mut [usize]) -> usize
+where
+    T: Ord,
+{
+    cycle_sort_forward_map(slice, perm);
+
+    perm.iter().enumerate().filter(|&(i, &p)| p != i).count()
+}
+
+/// Moves every element at `slice[i]` to position `perm[i]`, and returns the
+/// number of writes made.
+///
+/// Each position is written to at most once, and only if an element is
+/// moved to it from elsewhere, so applying a plan made by [`plan_sort`]
+/// makes as many writes as it predicted. The plan is followed one cycle at
+/// a time without any extra memory, by finding the smallest position of
+/// each cycle, which takes `O(n^2)` time in the worst case.
+///
+/// [`plan_sort`]: fn.plan_sort.html
+///
+/// # Panics
+///
+/// Panics if `perm` and `slice` have different lengths, or if `perm` holds
+/// a position out of bounds. If `perm` is not a permutation of
+/// `0..slice.len()`, the elements end up in an unspecified order, or this
+/// function panics.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::execute_plan;
+/// let mut a = [3, 1, 2];
+/// let     w = execute_plan(&mut a, &[2, 0, 1]);
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn execute_plan<T>(slice: &mut [T], perm: &[usize]) -> usize {
+    let length = slice.len();
+    let mut writes = 0;
+
+    assert_eq!(length, perm.len(), "perm length must equal slice length");
+
+    for start in 0..length {
+        // only follow a cycle from its smallest position, giving up once
+        // more steps are taken than there are positions
+        let mut i = perm[start];
+        let mut steps = 0;
+
+        while i > start {
+            steps += 1;
+            assert!(steps < length, "perm is not a permutation");
+            i = perm[i];
+        }
+
+        if i != start || perm[start] == start {
+            continue;
+        }
+
+        // the element in flight is exchanged with the one in the position
+        // it belongs at, so every position is written to once, and the
+        // cycle was just followed without panicking, so nothing is
+        // duplicated or lost
+        unsafe {
+            let base = slice.as_mut_ptr();
+            let mut tmp = ptr::read(base.add(start));
+            let mut i = perm[start];
+
+            loop {
+                tmp = ptr::replace(base.add(i), tmp);
+                writes += 1;
+
+                if i == start {
+                    mem::forget(tmp);
+                    break;
+                }
+                i = perm[i];
+            }
+        }
+    }
+
+    writes
+}
+
+/// Returns whether placing `slice[perm[i]]` at every position `i` would
+/// sort the slice, without modifying it.
+///
+/// This is the layout [`cycle_sort_audit`] records, and the inverse of the
+/// one [`plan_sort`] fills. It can be used to check that a stored
+/// permutation still sorts the data it was made for. If `perm` is not a
+/// permutation of `0..slice.len()`, this returns `false`. Checking that
+/// takes `O(n^2)` time.
+///
+/// [`cycle_sort_audit`]: fn.cycle_sort_audit.html
+/// [`plan_sort`]: fn.plan_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::permutation_sorts;
+/// assert!(permutation_sorts(&[3, 1, 2], &[1, 2, 0]));
+/// assert!(!permutation_sorts(&[3, 1, 2], &[2, 0, 1]));
+/// assert!(!permutation_sorts(&[3, 1, 2], &[1, 1, 0]));
+/// ```
+#[inline]
+pub fn permutation_sorts<T>(slice: &[T], perm: &[usize]) -> bool
+where
+    T: Ord,
+{
+    let length = slice.len();
+
+    let is_permutation = perm.len() == length
+        && perm
+            .iter()
+            .enumerate()
+            .all(|(i, &p)| p < length && !perm[..i].contains(&p));
+
+    is_permutation && perm.windows(2).all(|w| slice[w[0]] <= slice[w[1]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_sort;
+
+    extern crate std;
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+        let mut perm = [0; SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let predicted = plan_sort(&array[..length], &mut perm[..length]);
+            let writes = execute_plan(&mut array[..length], &perm[..length]);
+
+            assert_eq!(predicted, 0);
+            assert_eq!(writes, 0);
+            assert!(permutation_sorts(&array[..length], &perm[..length]));
+        }
+    }
+
+    #[test]
+    fn plan_then_execute() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u32; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 20;
+            }
+            let mut b = a;
+            let mut perm = [0; SIZE];
+
+            let expect = cycle_sort(&mut a[..length]);
+            let predicted = plan_sort(&b[..length], &mut perm[..length]);
+            let writes = execute_plan(&mut b[..length], &perm[..length]);
+
+            assert_eq!(predicted, expect);
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn execute_owned() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a: Vec<String> = (0..length)
+                .map(|_| rng.gen_range(0, 10).to_string())
+                .collect();
+            let mut b = a.clone();
+            let mut perm = [0; SIZE];
+
+            let expect = cycle_sort(&mut a[..]);
+            let predicted = plan_sort(&b, &mut perm[..length]);
+
+            assert_eq!(execute_plan(&mut b, &perm[..length]), predicted);
+            assert_eq!(predicted, expect);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn sorting_permutations() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u32; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 20;
+            }
+            let mut b = a;
+
+            let mut perm = [0; SIZE];
+            let _ = crate::cycle_sort_audit(&mut b[..length], &mut perm[..length]);
+
+            assert!(permutation_sorts(&a[..length], &perm[..length]));
+            assert!(!permutation_sorts(&a[..length], &perm[..length + 1]));
+
+            if length > 1 {
+                // the data changed since
+                let (i, j) = (perm[0], perm[length - 1]);
+                if a[i] != a[j] {
+                    a.swap(i, j);
+                    assert!(!permutation_sorts(&a[..length], &perm[..length]));
+                    a.swap(i, j);
+                }
+
+                // not a permutation
+                perm[0] = perm[1];
+                assert!(!permutation_sorts(&a[..length], &perm[..length]));
+                perm[0] = length;
+                assert!(!permutation_sorts(&a[..length], &perm[..length]));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "perm is not a permutation")]
+    fn not_a_permutation() {
+        let _ = execute_plan(&mut [1, 2, 3], &[1, 2, 2]);
+    }
+}
@@ -0,0 +1,143 @@
+#![deny(missing_docs)]
+
+/// A reusable buffer for the functions taking a caller-provided buffer of
+/// the same length as the slice being sorted.
+///
+/// Functions such as [`cycle_sort_forward_map`], [`plan_sort`] or
+/// [`cycle_sort_moved_mask`] take a buffer that must be exactly as long as
+/// the slice, and panic otherwise. A `Scratch` wraps a single buffer large
+/// enough for the longest slice, and hands out its prefix of the right
+/// length for each call, so the same memory can be reused across calls
+/// and slice lengths without sizing it every time.
+///
+/// The prefix handed out keeps whatever values earlier calls left in it,
+/// which the functions taking a buffer overwrite anyway.
+///
+/// [`cycle_sort_forward_map`]: fn.cycle_sort_forward_map.html
+/// [`plan_sort`]: fn.plan_sort.html
+/// [`cycle_sort_moved_mask`]: fn.cycle_sort_moved_mask.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort_fixed_point_indices, plan_sort, Scratch};
+/// let mut buf = [0; 16];
+/// let mut scratch = Scratch::new(&mut buf);
+///
+/// let a = [3, 1, 2];
+/// let w = plan_sort(&a, scratch.for_slice(&a));
+///
+/// assert_eq!(w, 3);
+///
+/// let mut b = [1, 4, 1, 5, 9, 2];
+/// let     f = scratch.get(b.len());
+///
+/// assert_eq!(cycle_sort_fixed_point_indices(&mut b, f), (5, 1));
+/// ```
+#[derive(Debug)]
+pub struct Scratch<'a, T> {
+    buf: &'a mut [T],
+}
+
+impl<'a, T> Scratch<'a, T> {
+    /// Wraps a buffer to be reused.
+    #[inline]
+    pub fn new(buf: &'a mut [T]) -> Self {
+        Scratch { buf }
+    }
+
+    /// Returns the longest buffer that can be handed out.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns a buffer of length `len`, or `None` if the wrapped buffer is
+    /// shorter than that.
+    #[inline]
+    pub fn try_get(&mut self, len: usize) -> Option<&mut [T]> {
+        self.buf.get_mut(..len)
+    }
+
+    /// Returns a buffer of length `len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the wrapped buffer is shorter than `len`.
+    #[inline]
+    pub fn get(&mut self, len: usize) -> &mut [T] {
+        let capacity = self.buf.len();
+
+        match self.buf.get_mut(..len) {
+            Some(buf) => buf,
+            None => panic!(
+                "scratch buffer of length {} too short for {} elements",
+                capacity, len
+            ),
+        }
+    }
+
+    /// Returns a buffer as long as `slice`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the wrapped buffer is shorter than `slice`.
+    #[inline]
+    pub fn for_slice<U>(&mut self, slice: &[U]) -> &mut [T] {
+        self.get(slice.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cycle_sort, cycle_sort_audit, cycle_sort_forward_map, plan_sort};
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        let mut buf = [(); 1100];
+        let mut scratch = Scratch::new(&mut buf);
+
+        assert_eq!(scratch.capacity(), 1100);
+        assert_eq!(scratch.get(1000).len(), 1000);
+        assert_eq!(scratch.for_slice(&[(); 1100]).len(), 1100);
+        assert!(scratch.try_get(1101).is_none());
+    }
+
+    #[test]
+    fn reused() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+        let mut buf = [0; SIZE];
+        let mut scratch = Scratch::new(&mut buf);
+
+        for length in (0..SIZE + 1).rev() {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            let slice = &mut a[..length];
+
+            let mut map = [0; SIZE];
+            cycle_sort_forward_map(slice, &mut map[..length]);
+            cycle_sort_forward_map(slice, scratch.for_slice(slice));
+
+            assert_eq!(scratch.get(length), &map[..length]);
+
+            let predicted = plan_sort(slice, scratch.for_slice(slice));
+            let perm = scratch.get(length);
+            let writes = cycle_sort_audit(slice, perm);
+
+            assert_eq!(writes, predicted);
+            assert_eq!(cycle_sort(slice), 0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "scratch buffer of length 2 too short for 3 elements")]
+    fn too_short() {
+        let mut buf = [0; 2];
+        let _ = Scratch::new(&mut buf).for_slice(&[1, 2, 3]);
+    }
+}
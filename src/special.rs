@@ -0,0 +1,711 @@
+#![deny(missing_docs)]
+
+use core::cmp::Ordering;
+use core::{mem, slice};
+
+use crate::cycle_sort::cycle_impl_from;
+
+/// Sorts a slice of byte strings lexicographically and returns the number
+/// of writes made.
+///
+/// The prefix shared by all strings is found once up front and skipped in
+/// every comparison, which saves a lot of work when sorting strings with a
+/// long common prefix, such as paths or keys in the same namespace.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_lexico;
+/// let mut a: [&[u8]; 4] = [b"/usr/lib", b"/usr/bin", b"/usr", b"/usr/bin/env"];
+/// let     w = cycle_sort_lexico(&mut a);
+///
+/// assert_eq!(a, [&b"/usr"[..], b"/usr/bin", b"/usr/bin/env", b"/usr/lib"]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_lexico(slice: &mut [&[u8]]) -> usize {
+    lexico_impl(slice, &|a: &[u8], b: &[u8]| a < b)
+}
+
+/// Sorts byte strings with `is_less` comparing them past their common
+/// prefix.
+fn lexico_impl<F>(slice: &mut [&[u8]], is_less: &F) -> usize
+where
+    F: Fn(&[u8], &[u8]) -> bool,
+{
+    let prefix = match slice.split_first() {
+        Some((first, rest)) => rest.iter().fold(first.len(), |len, s| {
+            first[..len]
+                .iter()
+                .zip(s.iter())
+                .take_while(|(a, b)| a == b)
+                .count()
+        }),
+        None => return 0,
+    };
+
+    cycle_impl_from(slice, 0, &|a: &&[u8], b: &&[u8]| {
+        is_less(&a[prefix..], &b[prefix..])
+    })
+}
+
+/// Sorts a slice of characters by their Unicode scalar values and returns
+/// the number of writes made.
+///
+/// This is the order `char` implements [`Ord`] with. It is not the
+/// alphabetical order of any language, so for example all uppercase ASCII
+/// letters come before all lowercase ones, and accented letters come after
+/// both.
+///
+/// [`Ord`]: https://doc.rust-lang.org/std/cmp/trait.Ord.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_chars;
+/// let mut a = ['é', 'a', 'Z', 'λ', '1', 'Å'];
+/// let     w = cycle_sort_chars(&mut a);
+///
+/// // U+0031, U+005A, U+0061, U+00C5, U+00E9, U+03BB
+/// assert_eq!(a, ['1', 'Z', 'a', 'Å', 'é', 'λ']);
+/// assert_eq!(w, 6);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_chars(slice: &mut [char]) -> usize {
+    cycle_impl_from(slice, 0, &|a: &char, b: &char| {
+        u32::from(*a) < u32::from(*b)
+    })
+}
+
+/// Sorts a slice of fixed-point decimal numbers in ascending order and
+/// returns the number of writes made.
+///
+/// Every number is stored as an integer `x` standing for `x / 10^scale`,
+/// such as an amount in cents for a `scale` of `2`. Since all numbers share
+/// the same scale, their order is that of the integers, so this sorts
+/// exactly like [`cycle_sort`], but signals the intent at the call site and
+/// checks the scale.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Panics
+///
+/// Panics if `scale` is greater than `18`, since `10^scale` doesn't fit in
+/// an `i64` then, and no number but zero could be represented.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_fixed_point;
+/// // $12.50, -$0.99, $3.00 and $0.05 in cents
+/// let mut a = [1250, -99, 300, 5];
+/// let     w = cycle_sort_fixed_point(&mut a, 2);
+///
+/// assert_eq!(a, [-99, 5, 300, 1250]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_fixed_point(slice: &mut [i64], scale: u32) -> usize {
+    assert!(
+        10_i64.checked_pow(scale).is_some(),
+        "scale {} too large for i64 fixed-point numbers",
+        scale
+    );
+
+    cycle_impl_from(slice, 0, &|a: &i64, b: &i64| a < b)
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Integer types that can be sorted with [`cycle_sort_small_range`].
+///
+/// This trait is sealed and implemented for all primitive integer types
+/// up to 64 bits.
+///
+/// [`cycle_sort_small_range`]: fn.cycle_sort_small_range.html
+pub trait Integer: Copy + Ord + private::Sealed {
+    /// Returns `self - min`, which must not be negative.
+    fn distance(self, min: Self) -> u64;
+
+    /// Returns `self + distance`, which must not overflow.
+    fn advance(self, distance: u64) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($($unsigned:ty, $signed:ty;)*) => {$(
+        impl private::Sealed for $unsigned {}
+        impl private::Sealed for $signed {}
+
+        impl Integer for $unsigned {
+            #[inline]
+            fn distance(self, min: Self) -> u64 {
+                self.wrapping_sub(min) as u64
+            }
+
+            #[inline]
+            fn advance(self, distance: u64) -> Self {
+                self.wrapping_add(distance as $unsigned)
+            }
+        }
+
+        impl Integer for $signed {
+            #[inline]
+            fn distance(self, min: Self) -> u64 {
+                self.wrapping_sub(min) as $unsigned as u64
+            }
+
+            #[inline]
+            fn advance(self, distance: u64) -> Self {
+                self.wrapping_add(distance as $unsigned as $signed)
+            }
+        }
+    )*};
+}
+
+impl_integer! {
+    u8, i8;
+    u16, i16;
+    u32, i32;
+    u64, i64;
+    usize, isize;
+}
+
+/// The largest number of distinct values [`cycle_sort_small_range`]
+/// counts instead of cycle sorting.
+const BUCKETS: usize = 256;
+
+/// Sorts a slice of integers known to lie in `min..=max` and returns the
+/// number of writes made.
+///
+/// If the range holds at most 256 distinct values, the elements are
+/// counted and written back in order, which takes linear time. Otherwise
+/// this falls back to [`cycle_sort`]. Either way, only positions whose value
+/// changes are written to, so the writes made are the same as for
+/// [`cycle_sort`].
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Panics
+///
+/// Panics if `min > max`, or if the range is small enough to be counted
+/// and an element lies outside of it.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_small_range;
+/// let mut a = [1003_u16, 1001, 1000, 1003, 1002];
+/// let     w = cycle_sort_small_range(&mut a, 1000, 1003);
+///
+/// assert_eq!(a, [1000, 1001, 1002, 1003, 1003]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_small_range<T>(slice: &mut [T], min: T, max: T) -> usize
+where
+    T: Integer,
+{
+    assert!(min <= max, "range minimum must not exceed its maximum");
+
+    let span = max.distance(min);
+
+    if span >= BUCKETS as u64 {
+        return cycle_impl_from(slice, 0, &|a: &T, b: &T| a.lt(b));
+    }
+
+    let mut counts = [0_usize; BUCKETS];
+
+    for &x in slice.iter() {
+        assert!(min <= x && x <= max, "element out of range");
+        counts[x.distance(min) as usize] += 1;
+    }
+
+    let mut writes = 0;
+    let mut positions = slice.iter_mut();
+
+    for (distance, &count) in counts[..=span as usize].iter().enumerate() {
+        let value = min.advance(distance as u64);
+
+        for x in positions.by_ref().take(count) {
+            if *x != value {
+                *x = value;
+                writes += 1;
+            }
+        }
+    }
+
+    writes
+}
+
+/// Types with a byte representation, used by [`cycle_sort_deterministic`]
+/// to order elements that compare as equal.
+///
+/// For the primitive integer types this is their in-memory representation.
+/// Values that are equal must have equal bytes for sorting to be
+/// deterministic.
+///
+/// [`cycle_sort_deterministic`]: fn.cycle_sort_deterministic.html
+pub trait AsBytes {
+    /// Returns the bytes representing `self`.
+    fn as_bytes(&self) -> &[u8];
+}
+
+macro_rules! impl_as_bytes {
+    ($($t:ty)*) => {$(
+        impl AsBytes for $t {
+            #[inline]
+            fn as_bytes(&self) -> &[u8] {
+                // integers have no padding, so every byte is initialized
+                unsafe {
+                    slice::from_raw_parts(self as *const $t as *const u8, mem::size_of::<$t>())
+                }
+            }
+        }
+    )*};
+}
+
+impl_as_bytes! { u8 i8 u16 i16 u32 i32 u64 i64 u128 i128 usize isize }
+
+impl<const N: usize> AsBytes for [u8; N] {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering, ordering equal
+/// elements by their bytes, and returns the number of writes made.
+///
+/// Elements that compare as equal but have different bytes are ordered by
+/// comparing their bytes lexicographically, so the sorted slice only
+/// depends on which elements it holds and not on their order beforehand.
+/// This matters for types whose ordering ignores part of their value. For
+/// the primitive integers, equal elements are identical and this is the
+/// same as [`cycle_sort`].
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort_deterministic, AsBytes};
+/// # use std::cmp::Ordering;
+/// // words ordered regardless of case
+/// #[derive(Debug, PartialEq, Eq)]
+/// struct Word(&'static str);
+///
+/// impl Ord for Word {
+///     fn cmp(&self, other: &Self) -> Ordering {
+///         let a = self.0.bytes().map(|b| b.to_ascii_lowercase());
+///         let b = other.0.bytes().map(|b| b.to_ascii_lowercase());
+///         a.cmp(b)
+///     }
+/// }
+/// # impl PartialOrd for Word {
+/// #     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+/// #         Some(self.cmp(other))
+/// #     }
+/// # }
+///
+/// impl AsBytes for Word {
+///     fn as_bytes(&self) -> &[u8] {
+///         self.0.as_bytes()
+///     }
+/// }
+///
+/// let mut a = [Word("b"), Word("a"), Word("A")];
+/// let mut b = [Word("A"), Word("b"), Word("a")];
+///
+/// assert_eq!(cycle_sort_deterministic(&mut a), 2);
+/// assert_eq!(cycle_sort_deterministic(&mut b), 2);
+/// assert_eq!(a, [Word("A"), Word("a"), Word("b")]);
+/// assert_eq!(a, b);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_deterministic<T>(slice: &mut [T]) -> usize
+where
+    T: Ord + AsBytes,
+{
+    cycle_impl_from(slice, 0, &|a: &T, b: &T| {
+        a.cmp(b).then_with(|| a.as_bytes().cmp(b.as_bytes())) == Ordering::Less
+    })
+}
+
+/// Sorts a slice using the elements' natural ordering, moving sentinels to
+/// the end, and returns the number of writes made along with the number of
+/// sentinels, as `(writes, sentinel_count)`.
+///
+/// Elements for which `is_sentinel` returns `true` are placed after all
+/// others, and are treated as equal to each other, so they are only moved
+/// if they need to make room and end up in no particular order. The other
+/// elements are sorted as usual.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_with_sentinel;
+/// // readings where 0 means invalid
+/// let mut a = [7, 0, 3, 0, 5];
+/// let     r = cycle_sort_with_sentinel(&mut a, &|&x| x == 0);
+///
+/// assert_eq!(a, [3, 5, 7, 0, 0]);
+/// assert_eq!(r, (4, 2));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_with_sentinel<T, P>(slice: &mut [T], is_sentinel: &P) -> (usize, usize)
+where
+    T: Ord,
+    P: Fn(&T) -> bool,
+{
+    let count = slice.iter().filter(|x| is_sentinel(x)).count();
+
+    let writes = cycle_impl_from(
+        slice,
+        0,
+        &|a: &T, b: &T| match (is_sentinel(a), is_sentinel(b)) {
+            (false, false) => a < b,
+            (false, true) => true,
+            (true, _) => false,
+        },
+    );
+
+    (writes, count)
+}
+
+/// Sorts the elements of a slice not equal to `deleted` into its front,
+/// and returns the number of writes made along with the number of such
+/// elements, as `(writes, live_len)`.
+///
+/// `slice[..live_len]` ends up holding every live element in sorted order,
+/// and the rest of the slice the deleted ones. This is the same as
+/// [`cycle_sort_with_sentinel`] with the elements equal to `deleted` as
+/// sentinels.
+///
+/// [`cycle_sort_with_sentinel`]: fn.cycle_sort_with_sentinel.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_compact;
+/// let mut a = [7, -1, 3, -1, 5];
+/// let     r = cycle_sort_compact(&mut a, &-1);
+///
+/// assert_eq!(a[..r.1], [3, 5, 7]);
+/// assert_eq!(r, (4, 3));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_compact<T>(slice: &mut [T], deleted: &T) -> (usize, usize)
+where
+    T: Ord,
+{
+    let (writes, count) = cycle_sort_with_sentinel(slice, &|x| x.cmp(deleted) == Ordering::Equal);
+
+    (writes, slice.len() - count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_sort;
+
+    extern crate std;
+    use std::vec::Vec;
+
+    use rand::{seq::SliceRandom, thread_rng, Rng};
+
+    #[test]
+    fn common_prefix() {
+        const SIZE: usize = 64;
+
+        let mut rng = thread_rng();
+        let mut strings: Vec<Vec<u8>> = Vec::with_capacity(SIZE);
+
+        for prefix in &[0, 1, 100, 1000] {
+            strings.clear();
+
+            // strings sharing `prefix` bytes, followed by a few random ones
+            for _ in 0..SIZE {
+                let mut s = std::vec![b'x'; *prefix];
+                let suffix = rng.gen_range(0, 4);
+                s.extend((0..suffix).map(|_| rng.gen_range(b'a', b'd')));
+                strings.push(s);
+            }
+
+            let mut a: Vec<&[u8]> = strings.iter().map(|s| &s[..]).collect();
+            let mut b = a.clone();
+
+            let expect = cycle_sort(&mut a[..]);
+
+            assert_eq!(cycle_sort_lexico(&mut b[..]), expect);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn common_prefix_skipped() {
+        use core::cell::Cell;
+
+        const SIZE: usize = 64;
+        const PREFIX: usize = 100;
+
+        let mut rng = thread_rng();
+        let mut strings: Vec<Vec<u8>> = Vec::with_capacity(SIZE);
+
+        for _ in 0..SIZE {
+            let mut s = std::vec![b'x'; PREFIX];
+            s.extend((0..4).map(|_| rng.gen_range(b'a', b'd')));
+            strings.push(s);
+        }
+
+        // compares byte by byte, counting every byte compared
+        let bytes = Cell::new(0_usize);
+        let is_less = |a: &[u8], b: &[u8]| {
+            for (x, y) in a.iter().zip(b) {
+                bytes.set(bytes.get() + 1);
+                if x != y {
+                    return x < y;
+                }
+            }
+            a.len() < b.len()
+        };
+
+        let mut a: Vec<&[u8]> = strings.iter().map(|s| &s[..]).collect();
+        let mut b = a.clone();
+
+        let writes = cycle_impl_from(&mut a[..], 0, &|x: &&[u8], y: &&[u8]| is_less(x, y));
+        let baseline = bytes.replace(0);
+
+        assert_eq!(lexico_impl(&mut b[..], &is_less), writes);
+        assert_eq!(a, b);
+
+        // every comparison skips at least the shared prefix
+        let comparisons = crate::cycle_sort_ops(&mut strings.clone()[..]).comparisons;
+        assert!(bytes.get() + comparisons * PREFIX <= baseline);
+        assert!(bytes.get() < baseline);
+    }
+
+    #[test]
+    fn chars() {
+        const SIZE: usize = 100;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            // mostly ASCII, with some characters from other planes
+            let mut a: Vec<char> = (0..length)
+                .map(|_| match rng.gen_range(0, 4) {
+                    0 => rng.gen(),
+                    _ => rng.gen_range(b' ', b'~') as char,
+                })
+                .collect();
+            let mut b: Vec<u32> = a.iter().map(|&c| u32::from(c)).collect();
+
+            let expect = cycle_sort(&mut b[..]);
+
+            assert_eq!(cycle_sort_chars(&mut a[..]), expect);
+            assert!(a.iter().map(|&c| u32::from(c)).eq(b.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn fixed_point() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_i64; SIZE];
+            rng.fill(&mut a[..length]);
+            let b = a;
+
+            let expect = cycle_sort(&mut a[..length]);
+
+            for scale in 0..19 {
+                let mut c = b;
+                assert_eq!(cycle_sort_fixed_point(&mut c[..length], scale), expect);
+                assert_eq!(a, c);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "scale 19 too large for i64 fixed-point numbers")]
+    fn fixed_point_scale() {
+        let _ = cycle_sort_fixed_point(&mut [1, 2], 19);
+    }
+
+    #[test]
+    fn small_range() {
+        const SIZE: usize = 200;
+
+        let mut rng = thread_rng();
+
+        for length in (0..20).chain(190..SIZE) {
+            for &(min, max) in &[
+                (0_i32, 0),
+                (-5, 5),
+                (-1000, -745),
+                (-1000, -744),
+                (0, 1 << 20),
+            ] {
+                let mut a = [0_i32; SIZE];
+                for x in a[..length].iter_mut() {
+                    *x = rng.gen_range(min, max + 1);
+                }
+                let mut b = a;
+
+                let expect = cycle_sort(&mut a[..length]);
+
+                assert_eq!(cycle_sort_small_range(&mut b[..length], min, max), expect);
+                assert_eq!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn full_ranges() {
+        let mut a = [u8::MAX, 0, 7, u8::MAX];
+        let mut b = [i64::MAX, i64::MIN, 0];
+
+        assert_eq!(cycle_sort_small_range(&mut a, 0, u8::MAX), 3);
+        assert_eq!(a, [0, 7, u8::MAX, u8::MAX]);
+        assert_eq!(cycle_sort_small_range(&mut b, i64::MIN, i64::MAX), 3);
+        assert_eq!(b, [i64::MIN, 0, i64::MAX]);
+    }
+
+    #[test]
+    fn deterministic() {
+        const SIZE: usize = 40;
+
+        // pairs ordered by their first byte only
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        struct Pair([u8; 2]);
+
+        impl Ord for Pair {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0[0].cmp(&other.0[0])
+            }
+        }
+
+        impl PartialOrd for Pair {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl AsBytes for Pair {
+            fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [Pair([0; 2]); SIZE];
+            for x in a[..length].iter_mut() {
+                *x = Pair([rng.gen_range(0, 4), rng.gen_range(0, 4)]);
+            }
+
+            let mut expect = a;
+            expect[..length].sort_by_key(|p| p.0);
+
+            for _ in 0..5 {
+                a[..length].shuffle(&mut rng);
+                let mut b = a;
+
+                let _ = cycle_sort_deterministic(&mut b[..length]);
+                assert_eq!(b, expect);
+            }
+
+            let mut c = [0_i64; SIZE];
+            rng.fill(&mut c[..length]);
+            let mut d = c;
+
+            assert_eq!(
+                cycle_sort_deterministic(&mut c[..length]),
+                cycle_sort(&mut d[..length])
+            );
+            assert_eq!(c, d);
+        }
+    }
+
+    #[test]
+    fn sentinels() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+            let mut b = a;
+
+            // with sentinels mapped to the largest value, sorting makes the
+            // same writes
+            let is_sentinel = |x: &u8| x.is_multiple_of(3);
+            for x in a.iter_mut() {
+                if is_sentinel(x) {
+                    *x = u8::MAX;
+                }
+            }
+
+            let expect = cycle_sort(&mut a[..length]);
+            let (writes, count) = cycle_sort_with_sentinel(&mut b[..length], &is_sentinel);
+
+            assert_eq!(writes, expect);
+            assert_eq!(count, a[..length].iter().filter(|&&x| x == u8::MAX).count());
+            assert_eq!(a[..length - count], b[..length - count]);
+            assert!(b[length - count..length].iter().all(is_sentinel));
+        }
+
+        let mut z = [(); 1100];
+        assert_eq!(cycle_sort_with_sentinel(&mut z, &|_| true), (0, 1100));
+    }
+
+    #[test]
+    fn compact() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+            let mut b = a;
+
+            let (writes, live_len) = cycle_sort_compact(&mut a[..length], &3);
+            let expect = cycle_sort_with_sentinel(&mut b[..length], &|&x| x == 3);
+
+            assert_eq!((writes, length - live_len), expect);
+            assert_eq!(a, b);
+            assert!(a[..live_len].windows(2).all(|w| w[0] <= w[1]));
+            assert!(a[..live_len].iter().all(|&x| x != 3));
+            assert!(a[live_len..length].iter().all(|&x| x == 3));
+        }
+
+        let mut z = [(); 1100];
+        assert_eq!(cycle_sort_compact(&mut z, &()), (0, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range() {
+        let _ = cycle_sort_small_range(&mut [1, 2, 30], 0, 10);
+    }
+}
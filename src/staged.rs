@@ -0,0 +1,303 @@
+#![deny(missing_docs)]
+
+use core::cmp::Ordering;
+use core::mem;
+
+/// A sort of a slice that asks its caller to compare elements, returned by
+/// [`cycle_sort_staged`].
+///
+/// [`pair`] returns the positions of the next two elements to compare, and
+/// [`advance`] takes how they compare and sorts as far as possible without
+/// another comparison. Each pair depends on how the previous ones compared,
+/// but every element is always somewhere in the slice, which can be
+/// inspected with [`as_slice`] in between. Giving up before finishing
+/// leaves the slice partially sorted, but still holding every one of its
+/// elements exactly once.
+///
+/// Elements are exchanged with swaps, keeping the element being placed at
+/// the position its cycle started at, like [`cycle_sort_with_swap`] does.
+/// The writes counted are the same as for [`cycle_sort`], as long as the
+/// orderings reported describe a total order.
+///
+/// [`cycle_sort_staged`]: fn.cycle_sort_staged.html
+/// [`pair`]: struct.StagedSort.html#method.pair
+/// [`advance`]: struct.StagedSort.html#method.advance
+/// [`as_slice`]: struct.StagedSort.html#method.as_slice
+/// [`cycle_sort_with_swap`]: fn.cycle_sort_with_swap.html
+/// [`cycle_sort`]: fn.cycle_sort.html
+#[derive(Debug)]
+pub struct StagedSort<'a, T> {
+    slice: &'a mut [T],
+    /// The start of the current cycle, holding the element being placed.
+    src: usize,
+    /// The position the element being placed belongs at so far.
+    dst: usize,
+    /// The next position to compare the element being placed with.
+    next: usize,
+    /// Whether the element at `src` was swapped in during this cycle.
+    moved: bool,
+    /// Whether elements equal to the one being placed are being skipped.
+    skipping: bool,
+    writes: usize,
+}
+
+impl<'a, T> StagedSort<'a, T> {
+    /// Returns the positions `(a, b)` of the two elements to compare next,
+    /// or `None` if the slice is sorted.
+    ///
+    /// The positions are those of the elements at the time of the call,
+    /// and are reported to [`advance`] as the ordering of `slice[a]`
+    /// relative to `slice[b]`.
+    ///
+    /// [`advance`]: struct.StagedSort.html#method.advance
+    #[inline]
+    pub fn pair(&self) -> Option<(usize, usize)> {
+        if self.is_finished() {
+            None
+        } else if self.skipping {
+            Some((self.src, self.dst))
+        } else {
+            Some((self.next, self.src))
+        }
+    }
+
+    /// Reports how the elements at the positions returned by [`pair`]
+    /// compare, and continues sorting up to the next comparison.
+    ///
+    /// [`pair`]: struct.StagedSort.html#method.pair
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice is sorted already.
+    #[inline]
+    pub fn advance(&mut self, order: Ordering) {
+        assert!(!self.is_finished(), "sort already finished");
+
+        if self.skipping {
+            if order == Ordering::Equal {
+                self.dst += 1;
+            } else {
+                self.place();
+            }
+        } else {
+            if order == Ordering::Less {
+                self.dst += 1;
+            }
+            self.next += 1;
+        }
+
+        self.settle();
+    }
+
+    /// Returns whether the slice is sorted.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.src + 1 >= self.slice.len()
+    }
+
+    /// Returns the number of writes made so far.
+    #[inline]
+    pub fn writes(&self) -> usize {
+        self.writes
+    }
+
+    /// Returns the slice being sorted, in its current order.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.slice
+    }
+
+    /// Swaps the element being placed into the position it belongs at.
+    fn place(&mut self) {
+        self.slice.swap(self.src, self.dst);
+        self.writes += 1;
+        self.moved = true;
+        self.skipping = false;
+        self.dst = self.src;
+        self.next = self.src + 1;
+    }
+
+    /// Makes all progress possible without comparing elements.
+    fn settle(&mut self) {
+        let length = self.slice.len();
+
+        while !self.is_finished() {
+            if !self.skipping {
+                if self.next < length {
+                    return;
+                }
+
+                // the element at `src` belongs there, whether it was there
+                // to begin with or was swapped in
+                if self.dst == self.src {
+                    if self.moved {
+                        self.writes += 1;
+                    }
+
+                    self.src += 1;
+                    self.dst = self.src;
+                    self.next = self.src + 1;
+                    self.moved = false;
+                    continue;
+                }
+
+                self.skipping = true;
+            }
+
+            // place it after any possible duplicates, of which there can't
+            // be enough to reach past the last position
+            if self.dst + 1 < length {
+                return;
+            }
+
+            self.place();
+        }
+    }
+}
+
+/// Starts sorting a slice with comparisons made by the caller, and returns
+/// the state of the sort.
+///
+/// This inverts the control flow of sorting, so that each comparison can
+/// be made whenever and however the caller likes, such as after loading
+/// the elements from slow storage. See [`StagedSort`] for how to drive the
+/// sort. Any comparisons that aren't needed have already been skipped,
+/// so a slice that needs no comparisons at all is sorted right away.
+///
+/// [`StagedSort`]: struct.StagedSort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_staged;
+/// let mut a = [3, 1, 2];
+/// let mut s = cycle_sort_staged(&mut a);
+///
+/// while let Some((i, j)) = s.pair() {
+///     let order = s.as_slice()[i].cmp(&s.as_slice()[j]);
+///     s.advance(order);
+/// }
+///
+/// assert_eq!(s.writes(), 3);
+/// assert_eq!(a, [1, 2, 3]);
+/// ```
+#[inline]
+pub fn cycle_sort_staged<T>(slice: &mut [T]) -> StagedSort<'_, T> {
+    // all elements of zero-sized types are equal and stay in place
+    let src = if mem::size_of::<T>() == 0 {
+        slice.len()
+    } else {
+        0
+    };
+
+    let mut staged = StagedSort {
+        slice,
+        src,
+        dst: src,
+        next: src + 1,
+        moved: false,
+        skipping: false,
+        writes: 0,
+    };
+
+    staged.settle();
+    staged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_sort;
+
+    use rand::{thread_rng, Rng};
+
+    /// Drives `staged` to the end, comparing elements by their ordering.
+    fn drive<T: Ord>(staged: &mut StagedSort<'_, T>) {
+        while let Some((i, j)) = staged.pair() {
+            let slice = staged.as_slice();
+            let order = slice[i].cmp(&slice[j]);
+
+            staged.advance(order);
+        }
+    }
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let staged = cycle_sort_staged(&mut array[..length]);
+
+            assert!(staged.is_finished());
+            assert_eq!(staged.pair(), None);
+            assert_eq!(staged.writes(), 0);
+        }
+    }
+
+    #[test]
+    fn same_as_cycle_sort() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for divisor in &[2, 5, 255] {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= divisor;
+                }
+                let mut b = a;
+
+                let expect = cycle_sort(&mut a[..length]);
+
+                let mut staged = cycle_sort_staged(&mut b[..length]);
+                drive(&mut staged);
+
+                assert!(staged.is_finished());
+                assert_eq!(staged.writes(), expect);
+                assert_eq!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn stopped_early() {
+        const SIZE: usize = 30;
+
+        let mut rng = thread_rng();
+
+        for steps in 0..100 {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..]);
+            let mut b = a;
+
+            // answer at random for a while, then give up
+            let mut staged = cycle_sort_staged(&mut b);
+            for _ in 0..steps {
+                if staged.pair().is_none() {
+                    break;
+                }
+
+                staged.advance(match rng.gen_range(0, 3) {
+                    0 => Ordering::Less,
+                    1 => Ordering::Equal,
+                    _ => Ordering::Greater,
+                });
+            }
+
+            a.sort_unstable();
+            b.sort_unstable();
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "sort already finished")]
+    fn advance_finished() {
+        let mut a = [1];
+        cycle_sort_staged(&mut a).advance(Ordering::Less);
+    }
+}
@@ -0,0 +1,1562 @@
+#![deny(missing_docs)]
+
+use core::ops::{Add, AddAssign, ControlFlow};
+
+use crate::cycle_sort::{cycle_impl_hooked, Hook};
+use crate::keys::Mirror;
+use crate::permutation;
+use crate::util;
+use crate::CycleSortError;
+
+/// Runs a sort that cannot be stopped by `hook`.
+#[inline]
+fn observe<T, F, H>(slice: &mut [T], is_less: &F, hook: &mut H) -> usize
+where
+    F: Fn(&T, &T) -> bool,
+    H: Hook,
+{
+    match cycle_impl_hooked(slice, 0, is_less, hook) {
+        Ok(writes) => writes,
+        Err(_) => unreachable!(),
+    }
+}
+
+/// Marks every position written to.
+struct Moved<'a>(&'a mut [bool]);
+
+impl Hook for Moved<'_> {
+    #[inline]
+    fn write(&mut self, _src: usize, _from: usize, to: usize) -> ControlFlow<()> {
+        self.0[to] = true;
+        ControlFlow::Continue(())
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering, marks which
+/// positions were written to, and returns the number of writes made.
+///
+/// After sorting, `moved[i]` is `true` if and only if `slice[i]` was
+/// written to, so the number of `true`s equals the number of writes.
+///
+/// # Panics
+///
+/// Panics if `moved` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_moved_mask;
+/// let mut a = [1, 4, 1, 5, 9, 2];
+/// let mut m = [false; 6];
+/// let     w = cycle_sort_moved_mask(&mut a, &mut m);
+///
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+/// assert_eq!(m, [false, true, true, true, true, true]);
+/// assert_eq!(w, 5);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_moved_mask<T>(slice: &mut [T], moved: &mut [bool]) -> usize
+where
+    T: Ord,
+{
+    assert_eq!(
+        slice.len(),
+        moved.len(),
+        "mask length must equal slice length"
+    );
+
+    for m in moved.iter_mut() {
+        *m = false;
+    }
+
+    observe(slice, &|a, b| a.lt(b), &mut Moved(moved))
+}
+
+/// The number of operations made by a sort, as returned by
+/// [`cycle_sort_ops`].
+///
+/// Counts from several sorts can be added up with `+`.
+///
+/// [`cycle_sort_ops`]: fn.cycle_sort_ops.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OpCounts {
+    /// The number of elements read from the slice, as described for
+    /// [`cycle_sort_read_write_counts`].
+    ///
+    /// [`cycle_sort_read_write_counts`]: fn.cycle_sort_read_write_counts.html
+    pub reads: usize,
+    /// The number of elements written to the slice.
+    pub writes: usize,
+    /// The number of times an element being placed was compared against one
+    /// in the slice.
+    pub comparisons: usize,
+    /// The number of cycles followed, not counting elements already in
+    /// place.
+    pub cycles: usize,
+}
+
+impl Add for OpCounts {
+    type Output = OpCounts;
+
+    #[inline]
+    fn add(self, other: OpCounts) -> OpCounts {
+        OpCounts {
+            reads: self.reads + other.reads,
+            writes: self.writes + other.writes,
+            comparisons: self.comparisons + other.comparisons,
+            cycles: self.cycles + other.cycles,
+        }
+    }
+}
+
+impl AddAssign for OpCounts {
+    #[inline]
+    fn add_assign(&mut self, other: OpCounts) {
+        *self = *self + other;
+    }
+}
+
+/// Counts every operation made on the slice being sorted.
+#[derive(Default)]
+struct Ops(OpCounts);
+
+impl Hook for Ops {
+    #[inline]
+    fn start(&mut self, _src: usize) {
+        self.0.reads += 1;
+    }
+
+    #[inline]
+    fn step(&mut self) -> ControlFlow<()> {
+        self.0.reads += 1;
+        self.0.comparisons += 1;
+        ControlFlow::Continue(())
+    }
+
+    #[inline]
+    fn write(&mut self, src: usize, _from: usize, to: usize) -> ControlFlow<()> {
+        self.0.reads += 1;
+        if to == src {
+            self.0.cycles += 1;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering and returns the
+/// number of operations made.
+///
+/// This gives a clock-free measure of the cost of sorting, which is useful
+/// for benchmarking in `no_std` environments.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_ops;
+/// let mut a = [3, 1, 2];
+/// let     c = cycle_sort_ops(&mut a);
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(c.writes, 3);
+/// assert_eq!(c.cycles, 1);
+///
+/// let mut b = [1, 0];
+/// let     t = c + cycle_sort_ops(&mut b);
+///
+/// assert_eq!(t.writes, 5);
+/// assert_eq!(t.cycles, 2);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_ops<T>(slice: &mut [T]) -> OpCounts
+where
+    T: Ord,
+{
+    let mut ops = Ops::default();
+    let writes = observe(slice, &|a, b| a.lt(b), &mut ops);
+
+    OpCounts { writes, ..ops.0 }
+}
+
+/// Sorts a slice using the elements' natural ordering and returns the
+/// number of reads and writes made, as `(reads, writes)`.
+///
+/// A read is counted whenever an element is read from the slice, which
+/// happens
+///
+/// * when the element at the start of a cycle is taken out,
+/// * for every comparison, each of which compares the element being placed
+///   against one element of the slice, and
+/// * for every write, which takes out the element being overwritten to
+///   place it next.
+///
+/// The element being placed is held outside of the slice, so comparing it
+/// costs no reads of its own.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_read_write_counts;
+/// let mut a = [3, 1, 2];
+/// let     c = cycle_sort_read_write_counts(&mut a);
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(c, (14, 3));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_read_write_counts<T>(slice: &mut [T]) -> (usize, usize)
+where
+    T: Ord,
+{
+    let ops = cycle_sort_ops(slice);
+
+    (ops.reads, ops.writes)
+}
+
+/// Sorts a slice using the elements' natural ordering, fills `fixed` with
+/// the positions that were already holding their final elements, and
+/// returns the number of writes made along with the number of such
+/// positions, as `(writes, num_fixed)`.
+///
+/// The positions are written to `fixed[..num_fixed]` in increasing order,
+/// and are exactly the ones sorting doesn't write to. If `fixed` is too
+/// short to hold all of them, only the first ones are written, but the
+/// count returned is still the total. Finding them takes another `O(n^2)`
+/// comparisons before sorting.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_fixed_point_indices;
+/// let mut a = [1, 4, 1, 5, 9, 2];
+/// let mut f = [0; 6];
+/// let     r = cycle_sort_fixed_point_indices(&mut a, &mut f);
+///
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+/// assert_eq!(r, (5, 1));
+/// assert_eq!(f[..1], [0]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_fixed_point_indices<T>(slice: &mut [T], fixed: &mut [usize]) -> (usize, usize)
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    let mut count = 0;
+    for i in (0..slice.len()).filter(|&i| permutation::in_place(slice, i, &is_less)) {
+        if let Some(f) = fixed.get_mut(count) {
+            *f = i;
+        }
+        count += 1;
+    }
+
+    (observe(slice, &is_less, &mut ()), count)
+}
+
+/// Sorts a slice using the elements' natural ordering, records the value
+/// of every position written to in `undo`, and returns the number of
+/// writes made.
+///
+/// `undo[..writes]` ends up holding `(index, previous_value)` for every
+/// position written to, in increasing order of position. Since each
+/// position is written to at most once, assigning every `previous_value`
+/// back to `slice[index]`, in any order, restores the slice as it was
+/// before sorting. `undo` must be at least `writes` long for that: if it is
+/// too short, only the first positions are recorded and the log is
+/// incomplete. Recording them takes another `O(n^2)` comparisons before
+/// sorting.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_with_undo_log;
+/// let mut a = [3, 1, 2];
+/// let mut u = [(0, 0); 3];
+/// let     w = cycle_sort_with_undo_log(&mut a, &mut u);
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(u[..w], [(0, 3), (1, 1), (2, 2)]);
+///
+/// for &(i, x) in u[..w].iter().rev() {
+///     a[i] = x;
+/// }
+/// assert_eq!(a, [3, 1, 2]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_with_undo_log<T>(slice: &mut [T], undo: &mut [(usize, T)]) -> usize
+where
+    T: Ord + Clone,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    let moved = (0..slice.len()).filter(|&i| !permutation::in_place(slice, i, &is_less));
+    for (entry, i) in undo.iter_mut().zip(moved) {
+        *entry = (i, slice[i].clone());
+    }
+
+    observe(slice, &is_less, &mut ())
+}
+
+/// Sorts a slice using the elements' natural ordering, fills `perm` with
+/// the original position of every element, and returns the number of
+/// writes made.
+///
+/// Afterwards, `perm[i]` is the position the element now at `slice[i]` was
+/// at before sorting. `perm` is updated along with every move made while
+/// sorting, so it always describes exactly how the elements were moved.
+///
+/// # Panics
+///
+/// Panics if `perm` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_audit;
+/// let mut a = [3, 1, 2];
+/// let mut p = [0; 3];
+/// let     w = cycle_sort_audit(&mut a, &mut p);
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(p, [1, 2, 0]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_audit<T>(slice: &mut [T], perm: &mut [usize]) -> usize
+where
+    T: Ord,
+{
+    assert_eq!(
+        slice.len(),
+        perm.len(),
+        "perm length must equal slice length"
+    );
+
+    for (i, p) in perm.iter_mut().enumerate() {
+        *p = i;
+    }
+
+    observe(slice, &|a, b| a.lt(b), &mut Mirror(perm))
+}
+
+/// Sorts a slice using the elements' natural ordering and returns the
+/// number of writes made along with the position of the first duplicate,
+/// as `(writes, first_duplicate)`.
+///
+/// `first_duplicate` is the first position in the sorted slice holding an
+/// element equal to the one before it, or `None` if all elements are
+/// unique.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_first_duplicate;
+/// let mut a = [5, 3, 9, 3, 5];
+///
+/// assert_eq!(cycle_sort_first_duplicate(&mut a), (4, Some(1)));
+/// assert_eq!(a, [3, 3, 5, 5, 9]);
+///
+/// assert_eq!(cycle_sort_first_duplicate(&mut [2, 1]), (2, None));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_first_duplicate<T>(slice: &mut [T]) -> (usize, Option<usize>)
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+    let writes = observe(slice, &is_less, &mut ());
+
+    let first = slice
+        .windows(2)
+        .position(|w| util::are_equal(&w[0], &w[1], &is_less))
+        .map(|i| i + 1);
+
+    (writes, first)
+}
+
+/// Sorts a slice using the elements' natural ordering and checks that it
+/// ends up strictly increasing.
+///
+/// Returns `Ok` with the number of writes made if no two elements are
+/// equal. Otherwise the slice is sorted all the same, and
+/// `Err(CycleSortError::Unsorted)` with the first position holding an
+/// element equal to the one before it is returned, as found by
+/// [`cycle_sort_first_duplicate`]. This is useful for sorting keys that
+/// must be unique, such as those of an index.
+///
+/// [`cycle_sort_first_duplicate`]: fn.cycle_sort_first_duplicate.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort_assert_strict, CycleSortError};
+/// let mut a = [5, 3, 9, 1];
+///
+/// assert_eq!(cycle_sort_assert_strict(&mut a), Ok(3));
+/// assert_eq!(a, [1, 3, 5, 9]);
+///
+/// let mut b = [5, 3, 9, 3];
+///
+/// let     r = cycle_sort_assert_strict(&mut b);
+///
+/// assert_eq!(r, Err(CycleSortError::Unsorted { index: 1 }));
+/// assert_eq!(b, [3, 3, 5, 9]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_assert_strict<T>(slice: &mut [T]) -> Result<usize, CycleSortError>
+where
+    T: Ord,
+{
+    match cycle_sort_first_duplicate(slice) {
+        (writes, None) => Ok(writes),
+        (_, Some(index)) => Err(CycleSortError::Unsorted { index }),
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering and returns the
+/// number of writes made along with whether any two elements are equal, as
+/// `(writes, has_duplicates)`.
+///
+/// Equal elements end up next to each other, so after sorting, the slice
+/// is scanned for neighbors that are equal, stopping at the first pair. An
+/// empty or single-element slice has no duplicates.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_has_duplicates;
+/// let mut a = [5, 3, 9, 3];
+///
+/// assert_eq!(cycle_sort_has_duplicates(&mut a), (3, true));
+/// assert_eq!(a, [3, 3, 5, 9]);
+///
+/// assert_eq!(cycle_sort_has_duplicates(&mut [2, 1]), (2, false));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_has_duplicates<T>(slice: &mut [T]) -> (usize, bool)
+where
+    T: Ord,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+    let writes = observe(slice, &is_less, &mut ());
+
+    let has_duplicates = slice
+        .windows(2)
+        .any(|w| util::are_equal(&w[0], &w[1], &is_less));
+
+    (writes, has_duplicates)
+}
+
+/// Sorts a slice using the elements' natural ordering and returns the
+/// number of writes made and the number of elements less than `threshold`,
+/// as `(writes, partition_index)`.
+///
+/// Afterwards, `slice[..partition_index]` holds the elements less than
+/// `threshold`, and `slice[partition_index..]` the rest, including any
+/// equal to it. The index is found by binary search on the sorted slice.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_partition_point;
+/// let mut a = [5, 3, 9, 3, 5];
+///
+/// assert_eq!(cycle_sort_partition_point(&mut a, &5), (4, 2));
+/// assert_eq!(a, [3, 3, 5, 5, 9]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_partition_point<T>(slice: &mut [T], threshold: &T) -> (usize, usize)
+where
+    T: Ord,
+{
+    let writes = observe(slice, &|a, b| a.lt(b), &mut ());
+
+    (writes, slice.partition_point(|x| x < threshold))
+}
+
+/// Sorts a slice using the elements' natural ordering and returns the
+/// number of writes made and the rank of `query` among the elements, as
+/// `(writes, rank)`.
+///
+/// The rank is the number of elements strictly less than `query`, so it is
+/// the position of the first element equal to it, if there is one, and
+/// where it would be inserted otherwise. This is the same as
+/// [`cycle_sort_partition_point`], for queries like percentiles, and takes
+/// `O(log n)` comparisons after sorting.
+///
+/// [`cycle_sort_partition_point`]: fn.cycle_sort_partition_point.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_rank_of;
+/// let mut a = [50, 10, 40, 20, 40];
+///
+/// assert_eq!(cycle_sort_rank_of(&mut a, &40), (4, 2));
+/// assert_eq!(cycle_sort_rank_of(&mut a, &45), (0, 4));
+/// assert_eq!(a, [10, 20, 40, 40, 50]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_rank_of<T>(slice: &mut [T], query: &T) -> (usize, usize)
+where
+    T: Ord,
+{
+    cycle_sort_partition_point(slice, query)
+}
+
+/// Sums the weights of every position written to.
+struct Weighted<'a> {
+    weights: &'a [u32],
+    total: u64,
+}
+
+impl Hook for Weighted<'_> {
+    #[inline]
+    fn write(&mut self, _src: usize, _from: usize, to: usize) -> ControlFlow<()> {
+        self.total += u64::from(self.weights[to]);
+        ControlFlow::Continue(())
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering and returns the
+/// number of writes made along with their total cost, as
+/// `(writes, total_weight)`.
+///
+/// `weights[i]` is the cost of writing to position `i`, and `total_weight`
+/// is the sum of the costs of every position written to.
+///
+/// # Panics
+///
+/// Panics if `weights` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_weighted;
+/// let mut a = [1, 4, 1, 5, 9, 2];
+///
+/// assert_eq!(cycle_sort_weighted(&mut a, &[1, 1, 1, 10, 10, 10]), (5, 32));
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_weighted<T>(slice: &mut [T], weights: &[u32]) -> (usize, u64)
+where
+    T: Ord,
+{
+    assert_eq!(
+        slice.len(),
+        weights.len(),
+        "weights length must equal slice length"
+    );
+
+    let mut hook = Weighted { weights, total: 0 };
+    let writes = observe(slice, &|a, b| a.lt(b), &mut hook);
+
+    (writes, hook.total)
+}
+
+/// Counts the positions skipped because they hold a duplicate.
+struct Shifts(usize);
+
+impl Hook for Shifts {
+    #[inline]
+    fn skip(&mut self) {
+        self.0 += 1;
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering and returns the
+/// number of writes made and how many times an element was shifted past a
+/// duplicate, as `(writes, dup_shifts)`.
+///
+/// Before an element is written into its position, any elements equal to
+/// it already placed there are skipped over, one position at a time.
+/// `dup_shifts` counts those steps, and is always 0 for unique elements.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_dup_shifts;
+/// let mut a = [2, 2, 1, 2];
+///
+/// assert_eq!(cycle_sort_dup_shifts(&mut a), (2, 1));
+/// assert_eq!(a, [1, 2, 2, 2]);
+///
+/// assert_eq!(cycle_sort_dup_shifts(&mut [3, 1, 2]), (3, 0));
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_dup_shifts<T>(slice: &mut [T]) -> (usize, usize)
+where
+    T: Ord,
+{
+    let mut shifts = Shifts(0);
+    let writes = observe(slice, &|a, b| a.lt(b), &mut shifts);
+
+    (writes, shifts.0)
+}
+
+/// Counts the comparisons made while following each cycle.
+struct CycleComparisons<'a> {
+    out: &'a mut [usize],
+    cycles: usize,
+    comparisons: usize,
+}
+
+impl Hook for CycleComparisons<'_> {
+    #[inline]
+    fn start(&mut self, _src: usize) {
+        self.comparisons = 0;
+    }
+
+    #[inline]
+    fn step(&mut self) -> ControlFlow<()> {
+        self.comparisons += 1;
+        ControlFlow::Continue(())
+    }
+
+    #[inline]
+    fn write(&mut self, src: usize, _from: usize, to: usize) -> ControlFlow<()> {
+        if to == src {
+            if let Some(out) = self.out.get_mut(self.cycles) {
+                *out = self.comparisons;
+            }
+            self.cycles += 1;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering, fills
+/// `cmp_per_cycle` with the number of comparisons made while following
+/// each cycle, and returns the number of writes made along with the number
+/// of cycles, as `(writes, num_cycles)`.
+///
+/// Comparisons are counted like in [`cycle_sort_ops`], and the cycles are
+/// listed in the order they were followed in. Each count includes every
+/// scan over the rest of the slice made to find where an element of the
+/// cycle belongs, and every duplicate skipped on the way. Comparisons made
+/// to find that an element is in place already don't belong to any cycle.
+/// If `cmp_per_cycle` is too short to hold all counts, only the first ones
+/// are written, but the number of cycles returned is still the total.
+///
+/// [`cycle_sort_ops`]: fn.cycle_sort_ops.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_cycle_comparisons;
+/// let mut a = [3, 1, 2, 5, 4];
+/// let mut c = [0; 2];
+/// let     r = cycle_sort_cycle_comparisons(&mut a, &mut c);
+///
+/// assert_eq!(a, [1, 2, 3, 4, 5]);
+/// assert_eq!(r, (5, 2));
+/// assert_eq!(c, [14, 3]);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_cycle_comparisons<T>(
+    slice: &mut [T],
+    cmp_per_cycle: &mut [usize],
+) -> (usize, usize)
+where
+    T: Ord,
+{
+    let mut hook = CycleComparisons {
+        out: cmp_per_cycle,
+        cycles: 0,
+        comparisons: 0,
+    };
+    let writes = observe(slice, &|a, b| a.lt(b), &mut hook);
+
+    (writes, hook.cycles)
+}
+
+/// Sorts a slice using the elements' natural ordering, fills `lows` and
+/// `highs` with the final positions of its `k` smallest and `k` largest
+/// elements, and returns the number of writes made.
+///
+/// `lows` is filled smallest first and `highs` largest first. Only the
+/// first `k` entries of each are written to.
+///
+/// # Panics
+///
+/// Panics if `k` exceeds the length of `slice`, `lows` or `highs`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_extremes;
+/// let mut a = [1, 4, 1, 5, 9, 2];
+/// let mut l = [0; 2];
+/// let mut h = [0; 2];
+/// let     w = cycle_sort_extremes(&mut a, 2, &mut l, &mut h);
+///
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+/// assert_eq!((l, h), ([0, 1], [5, 4]));
+/// assert_eq!(w, 5);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_extremes<T>(
+    slice: &mut [T],
+    k: usize,
+    lows: &mut [usize],
+    highs: &mut [usize],
+) -> usize
+where
+    T: Ord,
+{
+    let length = slice.len();
+
+    assert!(k <= length, "k must not exceed slice length");
+    assert!(
+        k <= lows.len() && k <= highs.len(),
+        "k must not exceed buffer lengths"
+    );
+
+    let writes = observe(slice, &|a, b| a.lt(b), &mut ());
+
+    for (i, (low, high)) in lows.iter_mut().zip(highs.iter_mut()).take(k).enumerate() {
+        *low = i;
+        *high = length - 1 - i;
+    }
+
+    writes
+}
+
+/// Sorts a slice using the elements' natural ordering, fills `sums` with
+/// the running totals of the sorted slice, and returns the number of
+/// writes made.
+///
+/// Afterwards, `sums[i]` is the sum of `slice[..=i]`, starting from
+/// `T::default()`. Overflow behaves as it does for `+` on `T`.
+///
+/// # Panics
+///
+/// Panics if `sums` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_prefix_sums;
+/// let mut a = [3, 1, 4, 1, 5];
+/// let mut s = [0; 5];
+/// let     w = cycle_sort_prefix_sums(&mut a, &mut s);
+///
+/// assert_eq!(a, [1, 1, 3, 4, 5]);
+/// assert_eq!(s, [1, 2, 5, 9, 14]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_prefix_sums<T>(slice: &mut [T], sums: &mut [T]) -> usize
+where
+    T: Ord + Copy + Add<Output = T> + Default,
+{
+    assert_eq!(
+        slice.len(),
+        sums.len(),
+        "sums length must equal slice length"
+    );
+
+    let writes = observe(slice, &|a, b| a.lt(b), &mut ());
+
+    let mut total = T::default();
+    for (sum, &x) in sums.iter_mut().zip(slice.iter()) {
+        total = total + x;
+        *sum = total;
+    }
+
+    writes
+}
+
+/// Counts the writes made in every cycle before each element is written.
+struct Flight<'a> {
+    flight: &'a mut [usize],
+    writes: usize,
+}
+
+impl Hook for Flight<'_> {
+    #[inline]
+    fn start(&mut self, _src: usize) {
+        self.writes = 0;
+    }
+
+    #[inline]
+    fn write(&mut self, _src: usize, from: usize, _to: usize) -> ControlFlow<()> {
+        // every position a cycle takes an element from still holds the
+        // element it started out with
+        self.flight[from] = self.writes;
+        self.writes += 1;
+        ControlFlow::Continue(())
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering, records how long
+/// every element was in flight, and returns the number of writes made.
+///
+/// A cycle places the elements it moves one after another, each taking the
+/// place of the next, so every element waits for the writes placing those
+/// before it in its cycle to be made. After sorting, `flight[i]` is the
+/// number of writes made in its cycle before the element originally at
+/// position `i` was written to its final position: `0` for the first
+/// element of a cycle, up to one less than the cycle's length for the
+/// last. Elements that are not moved get `0` as well.
+///
+/// # Panics
+///
+/// Panics if `flight` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_flight_times;
+/// let mut a = [3, 1, 2, 4];
+/// let mut f = [9; 4];
+/// let     w = cycle_sort_flight_times(&mut a, &mut f);
+///
+/// assert_eq!(a, [1, 2, 3, 4]);
+/// assert_eq!(f, [0, 2, 1, 0]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_flight_times<T>(slice: &mut [T], flight: &mut [usize]) -> usize
+where
+    T: Ord,
+{
+    assert_eq!(
+        slice.len(),
+        flight.len(),
+        "flight length must equal slice length"
+    );
+
+    for f in flight.iter_mut() {
+        *f = 0;
+    }
+
+    observe(slice, &|a, b| a.lt(b), &mut Flight { flight, writes: 0 })
+}
+
+/// Marks the positions written to by the first write of every cycle.
+struct Seeds<'a>(&'a mut [bool]);
+
+impl Hook for Seeds<'_> {
+    #[inline]
+    fn write(&mut self, src: usize, from: usize, to: usize) -> ControlFlow<()> {
+        self.0[to] = from == src;
+        ControlFlow::Continue(())
+    }
+}
+
+/// Sorts a slice using the elements' natural ordering, marks which
+/// positions were written to first in their cycles, and returns the number
+/// of writes made.
+///
+/// Every cycle starts by taking out the element at its first position and
+/// placing it, which is its seed write, and continues by placing each
+/// element that one displaced, which are its cascade writes, up to the one
+/// belonging at the first position. After sorting, `first_placement[i]` is
+/// `true` if and only if `slice[i]` was written to by the seed write of
+/// its cycle. So the number of `true`s is the number of cycles followed,
+/// not counting elements already in place, and every other write is a
+/// cascade write.
+///
+/// # Panics
+///
+/// Panics if `first_placement` and `slice` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_cascade_report;
+/// let mut a = [2, 1, 4, 5, 3];
+/// let mut f = [false; 5];
+/// let     w = cycle_sort_cascade_report(&mut a, &mut f);
+///
+/// assert_eq!(a, [1, 2, 3, 4, 5]);
+/// assert_eq!(f, [false, true, false, true, false]);
+/// assert_eq!(w, 5);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_cascade_report<T>(slice: &mut [T], first_placement: &mut [bool]) -> usize
+where
+    T: Ord,
+{
+    assert_eq!(
+        slice.len(),
+        first_placement.len(),
+        "mask length must equal slice length"
+    );
+
+    for f in first_placement.iter_mut() {
+        *f = false;
+    }
+
+    observe(slice, &|a, b| a.lt(b), &mut Seeds(first_placement))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+        let mut moved = [true; SIZE];
+
+        let mut lows = [0; SIZE];
+        let mut highs = [0; SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let writes = cycle_sort_moved_mask(&mut array[..length], &mut moved[..length]);
+
+            assert_eq!(writes, 0);
+            assert!(moved[..length].iter().all(|&m| !m));
+
+            assert_eq!(cycle_sort_read_write_counts(&mut array[..length]), (0, 0));
+            assert_eq!(cycle_sort_ops(&mut array[..length]), OpCounts::default());
+            assert_eq!(
+                cycle_sort_first_duplicate(&mut array[..length]),
+                (0, if length > 1 { Some(1) } else { None })
+            );
+            assert_eq!(cycle_sort_dup_shifts(&mut array[..length]), (0, 0));
+            assert_eq!(
+                cycle_sort_assert_strict(&mut array[..length]),
+                if length > 1 {
+                    Err(CycleSortError::Unsorted { index: 1 })
+                } else {
+                    Ok(0)
+                }
+            );
+            assert_eq!(
+                cycle_sort_has_duplicates(&mut array[..length]),
+                (0, length > 1)
+            );
+            assert_eq!(
+                cycle_sort_cycle_comparisons(&mut array[..length], &mut lows),
+                (0, 0)
+            );
+            assert_eq!(
+                cycle_sort_audit(&mut array[..length], &mut lows[..length]),
+                0
+            );
+            assert!((0..length).all(|i| lows[i] == i));
+            assert_eq!(
+                cycle_sort_weighted(&mut array[..length], &[1; SIZE][..length]),
+                (0, 0)
+            );
+            assert_eq!(cycle_sort_with_undo_log(&mut array[..length], &mut []), 0);
+            assert_eq!(
+                cycle_sort_flight_times(&mut array[..length], &mut lows[..length]),
+                0
+            );
+            assert_eq!(
+                cycle_sort_cascade_report(&mut array[..length], &mut moved[..length]),
+                0
+            );
+            assert_eq!(
+                cycle_sort_partition_point(&mut array[..length], &()),
+                (0, 0)
+            );
+            assert_eq!(cycle_sort_rank_of(&mut array[..length], &()), (0, 0));
+            assert_eq!(
+                cycle_sort_fixed_point_indices(&mut array[..length], &mut lows),
+                (0, length)
+            );
+
+            let k = length / 2;
+            let writes = cycle_sort_extremes(&mut array[..length], k, &mut lows, &mut highs);
+
+            assert_eq!(writes, 0);
+        }
+    }
+
+    #[test]
+    fn moved_mask() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+
+            let before = a;
+            let mut moved = [true; SIZE];
+            let writes = cycle_sort_moved_mask(&mut a[..length], &mut moved[..length]);
+
+            assert_eq!(moved[..length].iter().filter(|&&m| m).count(), writes);
+
+            // unmoved positions kept their value, moved ones changed it
+            for i in 0..length {
+                assert_eq!(moved[i], a[i] != before[i]);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn mask_length_mismatch() {
+        let _ = cycle_sort_moved_mask(&mut [2, 1], &mut [false; 3]);
+    }
+
+    #[test]
+    fn read_counts() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+            let mut b = a;
+
+            let writes = crate::cycle_sort(&mut a[..length]);
+            let (reads, counted) = cycle_sort_read_write_counts(&mut b[..length]);
+
+            assert_eq!(a, b);
+            assert_eq!(counted, writes);
+
+            // every element but the last is taken out once and compared
+            // at least against all elements after it
+            if length > 1 {
+                assert!(reads >= length - 1 + length * (length - 1) / 2 + writes);
+            } else {
+                assert_eq!(reads, 0);
+            }
+        }
+
+        // sorted input only reads to count
+        let mut c = [0, 1, 2, 3];
+        assert_eq!(cycle_sort_read_write_counts(&mut c), (3 + 6, 0));
+    }
+
+    #[test]
+    fn op_counts() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+        let mut total = OpCounts::default();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+            let mut b = a;
+
+            let cycles = crate::cycle_sort_parity(&a[..length]);
+            let ops = cycle_sort_ops(&mut a[..length]);
+
+            assert_eq!(
+                cycle_sort_read_write_counts(&mut b[..length]),
+                (ops.reads, ops.writes)
+            );
+            assert_eq!(ops.writes, cycles.0);
+            assert_eq!(
+                ops.reads,
+                length.saturating_sub(1) + ops.comparisons + ops.writes
+            );
+
+            total += ops;
+        }
+
+        let mut c = [4, 3, 2, 1, 0];
+
+        assert_eq!(cycle_sort_ops(&mut c).cycles, 2);
+        assert!(total.writes > 0);
+    }
+
+    #[test]
+    fn prefix_sums() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u32; SIZE];
+            for x in a[..length].iter_mut() {
+                *x = rng.gen_range(0, 100);
+            }
+            let mut b = a;
+            let mut sums = [0; SIZE];
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+            let writes = cycle_sort_prefix_sums(&mut b[..length], &mut sums[..length]);
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+
+            for i in 0..length {
+                assert_eq!(sums[i], b[..=i].iter().sum());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn sums_length_mismatch() {
+        let _ = cycle_sort_prefix_sums(&mut [2, 1], &mut [0; 1]);
+    }
+
+    #[test]
+    fn fixed_points() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 8;
+            }
+            let mut b = a;
+            let mut c = a;
+
+            let mut moved = [false; SIZE];
+            let mut fixed = [0; SIZE];
+            let expect = cycle_sort_moved_mask(&mut a[..length], &mut moved[..length]);
+            let (writes, count) = cycle_sort_fixed_point_indices(&mut b[..length], &mut fixed);
+
+            assert_eq!(a, b);
+            assert_eq!(writes, expect);
+            assert_eq!(count, length - writes);
+
+            let mut unmoved = (0..length).filter(|&i| !moved[i]);
+            assert!(fixed[..count].iter().copied().eq(unmoved.by_ref()));
+
+            // a short buffer only gets the first positions
+            let mut short = [usize::MAX; 2];
+            let (_, total) = cycle_sort_fixed_point_indices(&mut c[..length], &mut short);
+
+            assert_eq!(total, count);
+            for i in 0..2 {
+                let expect = if i < count { fixed[i] } else { usize::MAX };
+                assert_eq!(short[i], expect);
+            }
+        }
+    }
+
+    #[test]
+    fn first_duplicate() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 64;
+            }
+            let mut b = a;
+            let mut d = a;
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+            let (writes, first) = cycle_sort_first_duplicate(&mut b[..length]);
+
+            assert_eq!(a, b);
+            assert_eq!(writes, expect);
+
+            match first {
+                Some(i) => {
+                    assert_eq!(b[i - 1], b[i]);
+                    assert!(crate::is_sorted_unique(&b[..i]));
+                }
+                None => assert!(crate::is_sorted_unique(&b[..length])),
+            }
+
+            let mut c = a;
+            let (writes, has_duplicates) = cycle_sort_has_duplicates(&mut c[..length]);
+
+            assert_eq!(writes, 0);
+            assert_eq!(has_duplicates, first.is_some());
+
+            let strict = cycle_sort_assert_strict(&mut d[..length]);
+
+            assert_eq!(a, d);
+            let unsorted = |index| CycleSortError::Unsorted { index };
+
+            assert_eq!(strict, first.map(unsorted).map_or(Ok(expect), Err));
+        }
+    }
+
+    #[test]
+    fn audit() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+            let before = a;
+            let mut b = a;
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+
+            let mut perm = [usize::MAX; SIZE];
+            let writes = cycle_sort_audit(&mut b[..length], &mut perm[..length]);
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+
+            // a bijection that only moves written positions
+            let mut seen = [false; SIZE];
+            for i in 0..length {
+                assert!(!seen[perm[i]]);
+                seen[perm[i]] = true;
+                assert_eq!(b[i], before[perm[i]]);
+            }
+            assert_eq!((0..length).filter(|&i| perm[i] != i).count(), writes);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn audit_length_mismatch() {
+        let _ = cycle_sort_audit(&mut [2, 1], &mut [0]);
+    }
+
+    #[test]
+    fn undo_log() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+            let before = a;
+            let mut b = a;
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+
+            let mut undo = [(usize::MAX, 0); SIZE];
+            let writes = cycle_sort_with_undo_log(&mut b[..length], &mut undo);
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+            assert!(undo[writes..].iter().all(|&(i, _)| i == usize::MAX));
+
+            for &(i, x) in undo[..writes].iter().rev() {
+                b[i] = x;
+            }
+            assert_eq!(b, before);
+
+            // a short log is truncated
+            let mut c = before;
+            let mut short = [(usize::MAX, 0); 2];
+            let writes = cycle_sort_with_undo_log(&mut c[..length], &mut short);
+
+            assert_eq!(writes, expect);
+            assert_eq!(short[..writes.min(2)], undo[..writes.min(2)]);
+        }
+    }
+
+    #[test]
+    fn partition_point() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+            let b = a;
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+
+            for threshold in 0..17 {
+                let mut c = b;
+                let (writes, index) = cycle_sort_partition_point(&mut c[..length], &threshold);
+
+                assert_eq!(writes, expect);
+                assert_eq!(a, c);
+                assert_eq!(
+                    index,
+                    b[..length].iter().filter(|&&x| x < threshold).count()
+                );
+
+                let mut d = b;
+
+                assert_eq!(
+                    cycle_sort_rank_of(&mut d[..length], &threshold),
+                    (writes, index)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn weighted() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+            let mut b = a;
+            let mut c = a;
+
+            let mut weights = [0_u32; SIZE];
+            rng.fill(&mut weights[..]);
+
+            let mut moved = [false; SIZE];
+            let expect = cycle_sort_moved_mask(&mut a[..length], &mut moved[..length]);
+            let total = (0..length)
+                .filter(|&i| moved[i])
+                .map(|i| u64::from(weights[i]))
+                .sum();
+
+            assert_eq!(
+                cycle_sort_weighted(&mut b[..length], &weights[..length]),
+                (expect, total)
+            );
+            assert_eq!(a, b);
+
+            // unit weights count the writes
+            let ones = [1; SIZE];
+            assert_eq!(
+                cycle_sort_weighted(&mut c[..length], &ones[..length]),
+                (expect, expect as u64)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn weighted_length_mismatch() {
+        let _ = cycle_sort_weighted(&mut [2, 1], &[1]);
+    }
+
+    #[test]
+    fn cycle_comparisons() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 10;
+            }
+            let mut b = a;
+            let mut c = a;
+
+            let ops = cycle_sort_ops(&mut a[..length]);
+
+            let mut counts = [0; SIZE];
+            let (writes, cycles) = cycle_sort_cycle_comparisons(&mut b[..length], &mut counts);
+
+            assert_eq!(a, b);
+            assert_eq!(writes, ops.writes);
+            assert_eq!(cycles, ops.cycles);
+            assert!(counts[..cycles].iter().sum::<usize>() <= ops.comparisons);
+
+            // every element of a cycle is compared with the rest of the
+            // slice at least once
+            assert!(counts[..cycles].iter().all(|&n| n > 0));
+
+            // the same counts, cut short
+            let mut short = [0; 2];
+            let r = cycle_sort_cycle_comparisons(&mut c[..length], &mut short);
+
+            assert_eq!(r, (writes, cycles));
+            assert_eq!(short[..cycles.min(2)], counts[..cycles.min(2)]);
+        }
+    }
+
+    #[test]
+    fn dup_shifts() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            let mut b = a;
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+            let (writes, _) = cycle_sort_dup_shifts(&mut b[..length]);
+
+            assert_eq!(a, b);
+            assert_eq!(writes, expect);
+
+            // no duplicates to shift past
+            let mut c: [usize; SIZE] = core::array::from_fn(|i| i);
+            c[..length].reverse();
+
+            let (writes, shifts) = cycle_sort_dup_shifts(&mut c[..length]);
+
+            assert_eq!(writes, length / 2 * 2);
+            assert_eq!(shifts, 0);
+        }
+
+        // every duplicate is shifted past once
+        assert_eq!(cycle_sort_dup_shifts(&mut [1, 1, 1, 0]), (2, 2));
+    }
+
+    #[test]
+    fn extremes() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            let b = a;
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+
+            for k in 0..length + 1 {
+                let mut c = b;
+                let mut lows = [usize::MAX; SIZE];
+                let mut highs = [usize::MAX; SIZE];
+                let writes = cycle_sort_extremes(&mut c[..length], k, &mut lows, &mut highs);
+
+                assert_eq!(writes, expect);
+                assert_eq!(a, c);
+                assert!(lows[k..]
+                    .iter()
+                    .chain(&highs[k..])
+                    .all(|&i| i == usize::MAX));
+
+                for i in 0..k {
+                    assert_eq!(c[lows[i]], a[i]);
+                    assert_eq!(c[highs[i]], a[length - 1 - i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn extremes_too_many() {
+        let _ = cycle_sort_extremes(&mut [2, 1], 3, &mut [0; 3], &mut [0; 3]);
+    }
+
+    #[test]
+    fn flight_times() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            // a random permutation, whose cycles are easy to follow
+            let mut a = [0; SIZE];
+            for (i, x) in a.iter_mut().enumerate() {
+                *x = i;
+            }
+            for i in (1..length).rev() {
+                a.swap(i, rng.gen_range(0, i + 1));
+            }
+            let b = a;
+
+            // cycles start at their first position
+            let mut expect = [0; SIZE];
+            let mut seen = [false; SIZE];
+            for start in 0..length {
+                let mut i = start;
+                let mut writes = 0;
+                while !seen[i] {
+                    seen[i] = true;
+                    expect[i] = if b[i] == i { 0 } else { writes };
+                    writes += 1;
+                    i = b[i];
+                }
+            }
+
+            let mut flight = [0; SIZE];
+            let writes = cycle_sort_flight_times(&mut a[..length], &mut flight[..length]);
+
+            assert_eq!(writes, (0..length).filter(|&i| b[i] != i).count());
+            assert_eq!(flight, expect);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "flight length must equal slice length")]
+    fn flight_times_length() {
+        let _ = cycle_sort_flight_times(&mut [1, 2], &mut [0]);
+    }
+
+    #[test]
+    fn cascade_report() {
+        const SIZE: usize = 50;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            for divisor in &[2, 5, 255] {
+                let mut a = [0_u8; SIZE];
+                rng.fill(&mut a[..length]);
+                for x in a.iter_mut() {
+                    *x %= divisor;
+                }
+                let mut b = a;
+                let mut c = a;
+
+                let mut seeds = [true; SIZE];
+                let mut moved = [false; SIZE];
+                let writes = cycle_sort_cascade_report(&mut a[..length], &mut seeds[..length]);
+                let ops = cycle_sort_ops(&mut c[..length]);
+
+                assert_eq!(
+                    cycle_sort_moved_mask(&mut b[..length], &mut moved[..length]),
+                    writes
+                );
+                assert_eq!(a, b);
+
+                // seeds are written to, and there is one of them per cycle
+                assert!((0..length).all(|i| moved[i] || !seeds[i]));
+                assert_eq!(seeds[..length].iter().filter(|&&s| s).count(), ops.cycles);
+                assert_eq!(ops.writes, writes);
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+#![deny(missing_docs)]
+
+use core::cmp::Ordering;
+use core::mem;
+
+/// Sorts the first `length` elements of `data` by swapping them, and
+/// returns the number of positions written to.
+///
+/// `is_less(data, i, j)` compares the elements at positions `i` and `j`,
+/// and `swap(data, i, j)` exchanges them. The element being placed is kept
+/// at the position its cycle started at, rather than outside of `data`, so
+/// every position written to but the last one of each cycle gets its final
+/// element from a single swap. The destinations are the same as for
+/// `cycle_impl_hooked`.
+pub(crate) fn swap_impl<D, L, S>(data: &mut D, length: usize, is_less: &L, swap: &mut S) -> usize
+where
+    D: ?Sized,
+    L: Fn(&D, usize, usize) -> bool,
+    S: FnMut(&mut D, usize, usize),
+{
+    let mut writes = 0;
+
+    for src in 0..length.saturating_sub(1) {
+        let mut moved = false;
+
+        loop {
+            // count number of elements in `data[src..]` strictly less than
+            // the one at `src`
+            let mut dst = src;
+            for i in src + 1..length {
+                if is_less(data, i, src) {
+                    dst += 1;
+                }
+            }
+
+            // the element at `src` belongs there, whether it was there to
+            // begin with or was swapped in
+            if dst == src {
+                if moved {
+                    writes += 1;
+                }
+                break;
+            }
+
+            // place it after any possible duplicates, which can't include
+            // the one at `src` itself
+            while !is_less(data, src, dst) && !is_less(data, dst, src) {
+                dst += 1;
+            }
+
+            swap(data, src, dst);
+            writes += 1;
+            moved = true;
+        }
+    }
+
+    writes
+}
+
+/// Sorts a slice using a comparator function, exchanging elements with a
+/// custom swap function, and returns the number of writes made.
+///
+/// `is_less` must return whether its first argument is strictly less than
+/// its second. Every exchange is made by calling `swap(slice, a, b)`
+/// instead of swapping the elements directly, which makes it possible to
+/// log, mirror or otherwise hook every write. Each call must exchange the
+/// elements at positions `a` and `b`, and nothing else.
+///
+/// Each call writes its final element to one position, and the last call
+/// for each cycle to two positions. So the writes returned, which are the
+/// same as for [`cycle_sort_by`], are the number of calls made plus the
+/// number of cycles.
+///
+/// [`cycle_sort_by`]: fn.cycle_sort_by.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_with_swap;
+/// let mut a = [3, 1, 2];
+/// let mut log = Vec::new();
+/// let     w = cycle_sort_with_swap(&mut a, &|a, b| a < b, &mut |s: &mut [i32], i, j| {
+///     log.push((i, j));
+///     s.swap(i, j);
+/// });
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(log, [(0, 2), (0, 1)]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_with_swap<T, L, S>(slice: &mut [T], is_less: &L, swap: &mut S) -> usize
+where
+    L: Fn(&T, &T) -> bool,
+    S: FnMut(&mut [T], usize, usize),
+{
+    // all elements of zero-sized types are equal and stay in place
+    if mem::size_of::<T>() == 0 {
+        return 0;
+    }
+
+    let length = slice.len();
+
+    swap_impl(slice, length, &|s: &[T], i, j| is_less(&s[i], &s[j]), swap)
+}
+
+/// Sorts `len` rows stored in any layout, such as several parallel column
+/// slices, by comparing and swapping them through callbacks, and returns
+/// the number of writes made.
+///
+/// `compare_rows(i, j)` returns how rows `i` and `j` compare, reading
+/// whichever of their fields it needs, and `swap_row(i, j)` exchanges every
+/// field of both rows. Only the order of the rows is decided here, so the
+/// caller keeps full control over how they are stored and moved. Rows are
+/// swapped as described for [`cycle_sort_with_swap`], which makes the same
+/// writes as [`cycle_sort_by`], counting a row whose fields are all
+/// written as a single write. Since both callbacks access the rows, those
+/// can be held in [`Cell`]s, for example.
+///
+/// [`Cell`]: https://doc.rust-lang.org/core/cell/struct.Cell.html
+/// [`cycle_sort_with_swap`]: fn.cycle_sort_with_swap.html
+/// [`cycle_sort_by`]: fn.cycle_sort_by.html
+///
+/// # Examples
+///
+/// ```
+/// # use core::cell::Cell;
+/// # use cycle_sort::cycle_sort_soa;
+/// let ages = [42, 7, 42, 19].map(Cell::new);
+/// let names = ["ada", "bob", "cy", "dee"].map(Cell::new);
+///
+/// // by age, then by name in descending order
+/// let w = cycle_sort_soa(
+///     4,
+///     &|i, j| {
+///         let by_age = ages[i].get().cmp(&ages[j].get());
+///         by_age.then(names[j].get().cmp(names[i].get()))
+///     },
+///     &mut |i, j| {
+///         ages[i].swap(&ages[j]);
+///         names[i].swap(&names[j]);
+///     },
+/// );
+///
+/// assert_eq!(ages.map(Cell::into_inner), [7, 19, 42, 42]);
+/// assert_eq!(names.map(Cell::into_inner), ["bob", "dee", "cy", "ada"]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_soa<F, S>(len: usize, compare_rows: &F, swap_row: &mut S) -> usize
+where
+    F: Fn(usize, usize) -> Ordering,
+    S: FnMut(usize, usize),
+{
+    swap_impl(
+        &mut (),
+        len,
+        &|_: &(), i, j| compare_rows(i, j) == Ordering::Less,
+        &mut |_: &mut (), i, j| swap_row(i, j),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::cell::Cell;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            let writes = cycle_sort_with_swap(
+                &mut array[..length],
+                &|_, _| unreachable!(),
+                &mut |_, _, _| unreachable!(),
+            );
+
+            assert_eq!(writes, 0);
+        }
+    }
+
+    #[test]
+    fn swaps_match_cycle_sort() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+            let mut b = a;
+
+            let ops = crate::cycle_sort_ops(&mut a[..length]);
+
+            let mut calls = 0;
+            let writes = cycle_sort_with_swap(&mut b[..length], &|x, y| x < y, &mut |s, i, j| {
+                assert_ne!(i, j);
+                calls += 1;
+                s.swap(i, j);
+            });
+
+            assert_eq!(a, b);
+            assert_eq!(writes, ops.writes);
+            assert_eq!(calls + ops.cycles, writes);
+        }
+    }
+
+    #[test]
+    fn soa_matches_cycle_sort() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [(0_u8, 0_u8); SIZE];
+            for x in a[..length].iter_mut() {
+                *x = (rng.gen_range(0, 4), rng.gen_range(0, 4));
+            }
+
+            let mut keys = [0; SIZE];
+            let mut values = [0; SIZE];
+            for (i, &(k, v)) in a.iter().enumerate() {
+                keys[i] = k;
+                values[i] = v;
+            }
+
+            let expect = crate::cycle_sort(&mut a[..length]);
+
+            let keys = keys.map(Cell::new);
+            let values = values.map(Cell::new);
+            let row = |i: usize| (keys[i].get(), values[i].get());
+            let writes = cycle_sort_soa(length, &|i, j| row(i).cmp(&row(j)), &mut |i, j| {
+                assert_ne!(i, j);
+                keys[i].swap(&keys[j]);
+                values[i].swap(&values[j]);
+            });
+
+            assert_eq!(writes, expect);
+            for (i, &(k, v)) in a.iter().enumerate() {
+                assert_eq!(row(i), (k, v));
+            }
+        }
+    }
+}
@@ -1,3 +1,55 @@
+#[cfg(feature = "fast-eq")]
+use core::cmp::Ordering;
+use core::ptr;
+
+#[cfg(any(test, all(debug_assertions, feature = "check-permutation")))]
+use crate::cycle_sort::Slots;
+
+/// A strict weak ordering of `T`, given by whether one element is less than
+/// another.
+pub trait Less<T: ?Sized> {
+    /// Returns whether `a` is strictly less than `b`.
+    fn less(&self, a: &T, b: &T) -> bool;
+
+    /// Returns whether `a` and `b` are equal, which takes two comparisons
+    /// unless there is a cheaper way.
+    #[inline]
+    fn equal(&self, a: &T, b: &T) -> bool {
+        !self.less(a, b) && !self.less(b, a)
+    }
+}
+
+impl<T: ?Sized, F> Less<T> for F
+where
+    F: Fn(&T, &T) -> bool,
+{
+    #[inline(always)]
+    fn less(&self, a: &T, b: &T) -> bool {
+        self(a, b)
+    }
+}
+
+/// An ordering given by a comparator function, which tells equal elements
+/// apart with a single comparison.
+#[cfg(feature = "fast-eq")]
+pub struct ByOrdering<'a, F>(pub &'a F);
+
+#[cfg(feature = "fast-eq")]
+impl<T: ?Sized, F> Less<T> for ByOrdering<'_, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    #[inline(always)]
+    fn less(&self, a: &T, b: &T) -> bool {
+        (self.0)(a, b) == Ordering::Less
+    }
+
+    #[inline(always)]
+    fn equal(&self, a: &T, b: &T) -> bool {
+        (self.0)(a, b) == Ordering::Equal
+    }
+}
+
 #[inline]
 pub fn are_equal<T, F>(a: &T, b: &T, is_less: &F) -> bool
 where
@@ -6,6 +58,74 @@ where
     !is_less(a, b) && !is_less(b, a)
 }
 
+/// Puts the element at `slice[order[i]]` into every position `i`, writing
+/// only to positions whose element changes, and marking them done in
+/// `order`.
+pub fn apply_order<T>(slice: &mut [T], order: &mut [usize]) {
+    let base = slice.as_mut_ptr();
+
+    for start in 0..slice.len() {
+        if order[start] == start {
+            continue;
+        }
+
+        // every position of the cycle is moved out of before it's written
+        // to, and the element taken from `start` ends up last, so nothing
+        // is duplicated or lost, and nothing in between can panic
+        unsafe {
+            let tmp = ptr::read(base.add(start));
+            let mut i = start;
+
+            loop {
+                let next = order[i];
+                order[i] = i;
+
+                if next == start {
+                    ptr::write(base.add(i), tmp);
+                    break;
+                }
+
+                ptr::copy_nonoverlapping(base.add(next), base.add(i), 1);
+                i = next;
+            }
+        }
+    }
+}
+
+/// Summarizes the multiset of elements in `slots` by the ranks of its
+/// elements, which are unaffected by reordering them.
+///
+/// Returns the sum and the sum of squares of the number of elements strictly
+/// less than each element, and the number of ordered pairs of distinct
+/// positions holding equal elements.
+#[cfg(any(test, all(debug_assertions, feature = "check-permutation")))]
+pub fn rank_summary<S, F>(slots: &S, is_less: &F) -> (usize, usize, usize)
+where
+    S: Slots + ?Sized,
+    F: Fn(&S::Item, &S::Item) -> bool,
+{
+    let mut sum = 0_usize;
+    let mut squares = 0_usize;
+    let mut equal = 0;
+
+    for i in 0..slots.len() {
+        let mut rank = 0_usize;
+
+        for j in (0..slots.len()).filter(|&j| j != i) {
+            if is_less(slots.slot(j), slots.slot(i)) {
+                rank += 1;
+            } else if !is_less(slots.slot(i), slots.slot(j)) {
+                equal += 1;
+            }
+        }
+
+        sum = sum.wrapping_add(rank);
+        squares = squares.wrapping_add(rank.wrapping_mul(rank));
+    }
+
+    (sum, squares, equal)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -18,4 +138,23 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn summary() {
+        let is_less = |x: &i32, y: &i32| x < y;
+
+        let a = [3, 1, 2, 2];
+        let b = [2, 1, 2, 3];
+        let c = [3, 1, 1, 2];
+
+        assert_eq!(rank_summary(&a[..], &is_less), (5, 11, 2));
+        assert_eq!(
+            rank_summary(&a[..], &is_less),
+            rank_summary(&b[..], &is_less)
+        );
+        assert_ne!(
+            rank_summary(&a[..], &is_less),
+            rank_summary(&c[..], &is_less)
+        );
+    }
 }
@@ -0,0 +1,402 @@
+#![deny(missing_docs)]
+
+use alloc::vec::Vec;
+
+use crate::{cycle_sort, util};
+
+/// Collects an iterator into a vector, sorts it using the elements' natural
+/// ordering, and returns the vector along with the number of writes made,
+/// as `(vec, writes)`.
+///
+/// Requires the `alloc` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_from_iter;
+/// let (v, w) = cycle_sort_from_iter((0..10).rev());
+///
+/// assert_eq!(v, (0..10).collect::<Vec<_>>());
+/// assert_eq!(w, 10);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_from_iter<T, I>(iter: I) -> (Vec<T>, usize)
+where
+    T: Ord,
+    I: IntoIterator<Item = T>,
+{
+    let mut v: Vec<T> = iter.into_iter().collect();
+    let writes = cycle_sort(&mut v);
+
+    (v, writes)
+}
+
+/// Sorts a slice using the elements' natural ordering, and returns the
+/// number of writes made along with its runs of equal elements, as
+/// `(writes, runs)`.
+///
+/// Each run is a `(value, count)` pair holding a clone of the first element
+/// of the run and the number of elements in it, in sorted order. This is
+/// the run-length encoding of the sorted slice.
+///
+/// Requires the `alloc` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_rle;
+/// let mut a = [3, 1, 3, 3, 2, 1];
+/// let (w, r) = cycle_sort_rle(&mut a);
+///
+/// assert_eq!(a, [1, 1, 2, 3, 3, 3]);
+/// assert_eq!(r, [(1, 2), (2, 1), (3, 3)]);
+/// assert_eq!(w, 4);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_rle<T>(slice: &mut [T]) -> (usize, Vec<(T, usize)>)
+where
+    T: Ord + Clone,
+{
+    let is_less = |a: &T, b: &T| a.lt(b);
+
+    let writes = cycle_sort(slice);
+    let mut runs: Vec<(T, usize)> = Vec::new();
+
+    for x in slice.iter() {
+        match runs.last_mut() {
+            Some((value, count)) if util::are_equal(value, x, &is_less) => *count += 1,
+            _ => runs.push((x.clone(), 1)),
+        }
+    }
+
+    (writes, runs)
+}
+
+/// Returns whether `sorted` is in ascending order and holds the same
+/// elements as `original`, each as many times.
+///
+/// This checks the result of any sort against the input it was given, for
+/// use in fuzz targets and other correctness assertions. Sorting a copy of
+/// `original` with the standard library makes it take `O(n log n)` time
+/// rather than the `O(n^2)` of checking without allocating. Requires the
+/// `alloc` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::{cycle_sort, debug_check_sorted_permutation};
+/// let original = [1, 4, 1, 5, 9, 2];
+/// let mut a = original;
+/// let _ = cycle_sort(&mut a);
+///
+/// assert!(debug_check_sorted_permutation(&original, &a));
+/// assert!(!debug_check_sorted_permutation(&original, &[1, 1, 2, 4, 5, 5]));
+/// assert!(!debug_check_sorted_permutation(&original, &original));
+/// ```
+#[inline]
+pub fn debug_check_sorted_permutation<T>(original: &[T], sorted: &[T]) -> bool
+where
+    T: Ord + Clone,
+{
+    if original.len() != sorted.len() || !sorted.windows(2).all(|w| w[0] <= w[1]) {
+        return false;
+    }
+
+    let mut expected = original.to_vec();
+    expected.sort_unstable();
+
+    expected == sorted
+}
+
+/// A xorshift64* pseudorandom number generator, which is small and fast,
+/// but not suitable for anything but shuffling test data.
+struct XorShift(u64);
+
+impl XorShift {
+    #[inline]
+    fn new(seed: u64) -> Self {
+        // the state must never be zero
+        XorShift(seed ^ 0x9e37_79b9_7f4a_7c15 | 1)
+    }
+
+    #[inline]
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a number in `0..bound`, with negligible bias.
+    #[inline]
+    fn below(&mut self, bound: usize) -> usize {
+        ((self.next() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+/// Sorts `trials` random shuffles of a sample and returns the fewest,
+/// most and mean number of writes made, as `(min_writes, max_writes,
+/// mean_writes)`.
+///
+/// Each trial shuffles a copy of `sample` with a small pseudorandom number
+/// generator seeded with `seed`, so the results are reproducible, and
+/// sorts it with [`cycle_sort`]. The mean is rounded down. If `trials` is
+/// zero, all three are zero. Requires the `alloc` feature.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::write_count_stats;
+/// let (min, max, mean) = write_count_stats(&[1, 2, 3, 4, 5, 6, 7, 8], 1000, 42);
+///
+/// assert!(min <= mean && mean <= max && max <= 8);
+///
+/// // duplicates are never written over each other
+/// assert_eq!(write_count_stats(&[7; 8], 10, 42), (0, 0, 0));
+/// ```
+#[inline]
+pub fn write_count_stats<T>(sample: &[T], trials: usize, seed: u64) -> (usize, usize, usize)
+where
+    T: Ord + Clone,
+{
+    if trials == 0 {
+        return (0, 0, 0);
+    }
+
+    let mut rng = XorShift::new(seed);
+    let mut buf = sample.to_vec();
+    let (mut min, mut max, mut total) = (usize::MAX, 0, 0_u128);
+
+    for _ in 0..trials {
+        for i in (1..buf.len()).rev() {
+            buf.swap(i, rng.below(i + 1));
+        }
+
+        let writes = cycle_sort(&mut buf);
+
+        min = min.min(writes);
+        max = max.max(writes);
+        total += writes as u128;
+    }
+
+    (min, max, (total / trials as u128) as usize)
+}
+
+/// The length above which [`cycle_sort_or_std`] uses the standard library.
+///
+/// [`cycle_sort_or_std`]: fn.cycle_sort_or_std.html
+#[cfg(feature = "std")]
+const STD_THRESHOLD: usize = 256;
+
+/// Sorts a slice using the elements' natural ordering, with [`cycle_sort`]
+/// if it is short and with the standard library's unstable sort otherwise,
+/// and returns the number of positions whose value changed.
+///
+/// Slices of up to 256 elements are sorted with the fewest writes possible,
+/// as usual. Longer ones are sorted in `O(n log n)` time instead of
+/// `O(n^2)`, by sorting their positions with [`slice::sort_unstable_by`]
+/// and then moving every element that has to move exactly once. The count
+/// returned is the same either way, but for long slices, equal elements
+/// may be moved between each other's positions as well, which isn't
+/// counted. Requires the `std` feature.
+///
+/// [`cycle_sort`]: fn.cycle_sort.html
+/// [`slice::sort_unstable_by`]: https://doc.rust-lang.org/std/primitive.slice.html#method.sort_unstable_by
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_or_std;
+/// let mut a = [1, 4, 1, 5, 9, 2];
+///
+/// assert_eq!(cycle_sort_or_std(&mut a), 5);
+/// assert_eq!(a, [1, 1, 2, 4, 5, 9]);
+///
+/// let mut b: Vec<u32> = (0..1000).rev().collect();
+///
+/// assert_eq!(cycle_sort_or_std(&mut b), 1000);
+/// assert!(b.windows(2).all(|w| w[0] <= w[1]));
+/// ```
+#[cfg(feature = "std")]
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_or_std<T>(slice: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    let length = slice.len();
+
+    if length <= STD_THRESHOLD {
+        return cycle_sort(slice);
+    }
+
+    let mut order: Vec<usize> = (0..length).collect();
+    order.sort_unstable_by(|&i, &j| slice[i].cmp(&slice[j]));
+
+    let writes = order
+        .iter()
+        .enumerate()
+        .filter(|&(i, &j)| slice[i] != slice[j])
+        .count();
+
+    util::apply_order(slice, &mut order);
+
+    writes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::iter;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        for length in (0..10).chain(1000..1101) {
+            let (v, writes) = cycle_sort_from_iter(iter::repeat_n((), length));
+
+            assert_eq!(v.len(), length);
+            assert_eq!(writes, 0);
+
+            let mut v = v;
+            let (writes, runs) = cycle_sort_rle(&mut v);
+
+            assert_eq!(writes, 0);
+            #[cfg(feature = "std")]
+            assert_eq!(cycle_sort_or_std(&mut v), 0);
+            assert_eq!(write_count_stats(&v, 3, 0), (0, 0, 0));
+            assert!(debug_check_sorted_permutation(&v, &v));
+
+            if length == 0 {
+                assert!(runs.is_empty());
+            } else {
+                assert_eq!(runs, [((), length)]);
+            }
+        }
+    }
+
+    #[test]
+    fn from_iter() {
+        let mut rng = thread_rng();
+
+        for length in 0..50 {
+            let a: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+            let mut b = a.clone();
+
+            let expect = cycle_sort(&mut b);
+
+            assert_eq!(cycle_sort_from_iter(a), (b, expect));
+        }
+    }
+
+    #[test]
+    fn rle() {
+        let mut rng = thread_rng();
+
+        for length in 0..50 {
+            let mut a: Vec<u8> = (0..length).map(|_| rng.gen_range(0, 8)).collect();
+            let mut b = a.clone();
+
+            let expect = cycle_sort(&mut b);
+            let (writes, runs) = cycle_sort_rle(&mut a);
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+
+            // expanding the runs gives back the sorted slice
+            let expanded: Vec<u8> = runs
+                .iter()
+                .flat_map(|&(x, count)| iter::repeat_n(x, count))
+                .collect();
+
+            assert_eq!(expanded, b);
+            assert!(runs.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn check_sorted_permutation() {
+        let mut rng = thread_rng();
+
+        for length in 0..50 {
+            let original: Vec<u8> = (0..length).map(|_| rng.gen_range(0, 10)).collect();
+            let mut a = original.clone();
+            let _ = cycle_sort(&mut a);
+
+            assert!(debug_check_sorted_permutation(&original, &a));
+
+            if length > 0 {
+                // one element too few or too many
+                assert!(!debug_check_sorted_permutation(&original, &a[1..]));
+                assert!(!debug_check_sorted_permutation(&original[1..], &a));
+
+                // an element replaced, keeping the order
+                let mut b = a.clone();
+                b[length - 1] = 10;
+                assert!(!debug_check_sorted_permutation(&original, &b));
+            }
+
+            if let Some(i) = (1..length).find(|&i| a[i - 1] != a[i]) {
+                // out of order
+                a.swap(i - 1, i);
+                assert!(!debug_check_sorted_permutation(&original, &a));
+            }
+        }
+    }
+
+    #[test]
+    fn stats() {
+        let mut rng = thread_rng();
+
+        for length in 0..30 {
+            let sample: Vec<u8> = (0..length).map(|_| rng.gen_range(0, 10)).collect();
+            let seed = rng.gen();
+
+            let (min, max, mean) = write_count_stats(&sample, 200, seed);
+
+            assert!(min <= mean && mean <= max && max <= length);
+
+            // reproducible from the seed
+            assert_eq!(write_count_stats(&sample, 200, seed), (min, max, mean));
+            assert_eq!(write_count_stats(&sample, 0, seed), (0, 0, 0));
+        }
+
+        // a random permutation of distinct elements has one fixed point on
+        // average
+        let sample: Vec<u32> = (0..100).collect();
+        let (_, _, mean) = write_count_stats(&sample, 2000, 7);
+
+        assert!((98..=99).contains(&mean));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn or_std() {
+        let mut rng = thread_rng();
+
+        for &length in &[0, 1, 10, STD_THRESHOLD, STD_THRESHOLD + 1, 1000] {
+            for &divisor in &[2, 50, 255] {
+                let mut a: Vec<u8> = (0..length).map(|_| rng.gen::<u8>() % divisor).collect();
+                let mut b = a.clone();
+                let mut c = a.clone();
+
+                let expect = crate::predict_writes(&a);
+                let writes = cycle_sort_or_std(&mut a);
+                b.sort_unstable();
+
+                assert_eq!(writes, expect);
+                assert_eq!(a, b);
+
+                if length <= STD_THRESHOLD {
+                    assert_eq!(writes, cycle_sort(&mut c));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,977 @@
+#![deny(missing_docs)]
+
+use core::cell::Cell;
+use core::iter;
+
+use crate::cycle_sort;
+use crate::cycle_sort::{cycle_impl_from, Slots};
+
+/// A logical slice of a ring buffer that may wrap around its end.
+struct Wrapped<'a, T> {
+    buf: &'a mut [T],
+    start: usize,
+    len: usize,
+}
+
+impl<T> Wrapped<'_, T> {
+    #[inline(always)]
+    fn index(&self, i: usize) -> usize {
+        let j = self.start + i;
+
+        if j >= self.buf.len() {
+            j - self.buf.len()
+        } else {
+            j
+        }
+    }
+}
+
+impl<T> Slots for Wrapped<'_, T> {
+    type Item = T;
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    fn slot(&self, i: usize) -> &T {
+        &self.buf[self.index(i)]
+    }
+
+    #[inline(always)]
+    fn slot_mut(&mut self, i: usize) -> &mut T {
+        let j = self.index(i);
+        &mut self.buf[j]
+    }
+}
+
+/// A logical slice of every `stride`:th element of a buffer.
+struct Strided<'a, T> {
+    buf: &'a mut [T],
+    start: usize,
+    stride: usize,
+    len: usize,
+}
+
+impl<T> Slots for Strided<'_, T> {
+    type Item = T;
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    fn slot(&self, i: usize) -> &T {
+        &self.buf[self.start + i * self.stride]
+    }
+
+    #[inline(always)]
+    fn slot_mut(&mut self, i: usize) -> &mut T {
+        &mut self.buf[self.start + i * self.stride]
+    }
+}
+
+/// A slice whose second half is viewed in reverse.
+struct Bitonic<'a, T> {
+    buf: &'a mut [T],
+    mid: usize,
+}
+
+impl<T> Bitonic<'_, T> {
+    #[inline(always)]
+    fn index(&self, i: usize) -> usize {
+        if i < self.mid {
+            i
+        } else {
+            self.buf.len() - 1 - (i - self.mid)
+        }
+    }
+}
+
+impl<T> Slots for Bitonic<'_, T> {
+    type Item = T;
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    #[inline(always)]
+    fn slot(&self, i: usize) -> &T {
+        &self.buf[self.index(i)]
+    }
+
+    #[inline(always)]
+    fn slot_mut(&mut self, i: usize) -> &mut T {
+        let j = self.index(i);
+        &mut self.buf[j]
+    }
+}
+
+/// A logical slice of the elements of a buffer that are not kept fixed.
+///
+/// Elements are only moved between positions holding elements that are not
+/// kept fixed, so those positions never change. They are found by walking
+/// the buffer from the last one looked up, which is cheap for the mostly
+/// sequential accesses made while sorting.
+struct Filtered<'a, T, P> {
+    buf: &'a mut [T],
+    keep_fixed: &'a P,
+    len: usize,
+    cursor: Cell<(usize, usize)>,
+}
+
+impl<T, P> Filtered<'_, T, P>
+where
+    P: Fn(&T) -> bool,
+{
+    #[inline(always)]
+    fn index(&self, i: usize) -> usize {
+        let (mut k, mut j) = self.cursor.get();
+
+        while k < i {
+            j += 1;
+            if !(self.keep_fixed)(&self.buf[j]) {
+                k += 1;
+            }
+        }
+        while k > i {
+            j -= 1;
+            if !(self.keep_fixed)(&self.buf[j]) {
+                k -= 1;
+            }
+        }
+
+        self.cursor.set((k, j));
+        j
+    }
+}
+
+impl<T, P> Slots for Filtered<'_, T, P>
+where
+    P: Fn(&T) -> bool,
+{
+    type Item = T;
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    fn slot(&self, i: usize) -> &T {
+        &self.buf[self.index(i)]
+    }
+
+    #[inline(always)]
+    fn slot_mut(&mut self, i: usize) -> &mut T {
+        let j = self.index(i);
+        &mut self.buf[j]
+    }
+}
+
+/// A logical slice of the elements of a buffer that are not pinned.
+///
+/// `pinned` is in strictly ascending order, so `pinned[j] - j` is the
+/// number of positions not pinned before `pinned[j]`, which never
+/// decreases. The position of an element is found by binary searching
+/// that.
+struct Pinned<'a, T> {
+    buf: &'a mut [T],
+    pinned: &'a [usize],
+}
+
+impl<T> Pinned<'_, T> {
+    #[inline(always)]
+    fn index(&self, i: usize) -> usize {
+        let (mut lo, mut hi) = (0, self.pinned.len());
+
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.pinned[mid] - mid <= i {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        i + lo
+    }
+}
+
+impl<T> Slots for Pinned<'_, T> {
+    type Item = T;
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.buf.len() - self.pinned.len()
+    }
+
+    #[inline(always)]
+    fn slot(&self, i: usize) -> &T {
+        &self.buf[self.index(i)]
+    }
+
+    #[inline(always)]
+    fn slot_mut(&mut self, i: usize) -> &mut T {
+        let j = self.index(i);
+        &mut self.buf[j]
+    }
+}
+
+/// Sorts `len` elements of a ring buffer starting at index `start`, and
+/// returns the number of writes made.
+///
+/// The sorted sequence is `buf[start]`, `buf[(start + 1) % buf.len()]`,
+/// and so on, wrapping around the end of `buf`. Elements outside of it
+/// are left untouched.
+///
+/// # Panics
+///
+/// Panics if `len > buf.len()`, or if `start >= buf.len()` while `len` is
+/// non-zero.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_wrapped;
+/// // the logical sequence is [4, 1, 5, 9, 2]
+/// let mut a = [9, 2, 0, 4, 1, 5];
+/// let     w = cycle_sort_wrapped(&mut a, 3, 5);
+///
+/// assert_eq!(a, [5, 9, 0, 1, 2, 4]);
+/// assert_eq!(w, 5);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_wrapped<T>(buf: &mut [T], start: usize, len: usize) -> usize
+where
+    T: Ord,
+{
+    assert!(
+        len <= buf.len(),
+        "length {} out of range for buffer of length {}",
+        len,
+        buf.len()
+    );
+
+    if len == 0 {
+        return 0;
+    }
+
+    assert!(
+        start < buf.len(),
+        "start {} out of range for buffer of length {}",
+        start,
+        buf.len()
+    );
+
+    let mut view = Wrapped { buf, start, len };
+
+    cycle_impl_from(&mut view, 0, &|a: &T, b: &T| a.lt(b))
+}
+
+/// Sorts a slice into ascending order up to its middle and descending
+/// order after it, and returns the number of writes made.
+///
+/// The smallest `(n + 1) / 2` elements end up in `slice[..(n + 1) / 2]` in
+/// ascending order, and the rest in descending order after them, so the
+/// largest element is right after the middle, and odd lengths have the
+/// extra element in the first half. This is the same as sorting and
+/// reversing the second half, but the elements are moved to their final
+/// positions directly, making the fewest writes.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_bitonic;
+/// let mut a = [1, 2, 3, 4, 5];
+/// let     w = cycle_sort_bitonic(&mut a);
+///
+/// assert_eq!(a, [1, 2, 3, 5, 4]);
+/// assert_eq!(w, 2);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_bitonic<T>(slice: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    let mid = slice.len().div_ceil(2);
+    let mut view = Bitonic { buf: slice, mid };
+
+    cycle_impl_from(&mut view, 0, &|a: &T, b: &T| a.lt(b))
+}
+
+/// Sorts the elements of a slice for which `keep_fixed` returns `false`,
+/// leaving the others in place, and returns the number of writes made.
+///
+/// The positions holding elements for which `keep_fixed` returns `true`
+/// are never written to. The remaining positions, taken in order, end up
+/// holding the remaining elements in sorted order, so that they form a
+/// sorted subsequence of the slice. `keep_fixed` must return the same
+/// result for an element every time it is called.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_where;
+/// // sort everything but the zeros
+/// let mut a = [5, 0, 3, 1, 0, 4];
+/// let     w = cycle_sort_where(&mut a, &|&x| x == 0);
+///
+/// assert_eq!(a, [1, 0, 3, 4, 0, 5]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_where<T, P>(slice: &mut [T], keep_fixed: &P) -> usize
+where
+    T: Ord,
+    P: Fn(&T) -> bool,
+{
+    let len = slice.iter().filter(|x| !keep_fixed(x)).count();
+    let first = slice.iter().position(|x| !keep_fixed(x)).unwrap_or(0);
+
+    let mut view = Filtered {
+        buf: slice,
+        keep_fixed,
+        len,
+        cursor: Cell::new((0, first)),
+    };
+
+    cycle_impl_from(&mut view, 0, &|a: &T, b: &T| a.lt(b))
+}
+
+/// Sorts the elements of a slice that are not at pinned positions, and
+/// returns the number of writes made.
+///
+/// The elements at the positions in `pinned` are left where they are, and
+/// the others are sorted among the remaining positions, just like with
+/// [`cycle_sort_where`], but choosing the fixed elements by position rather
+/// than by value. Looking up a position takes `O(log k)` time for `k`
+/// pinned positions.
+///
+/// [`cycle_sort_where`]: fn.cycle_sort_where.html
+///
+/// # Panics
+///
+/// Panics if `pinned` is not in strictly ascending order, or holds a
+/// position out of bounds.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_pinned;
+/// let mut a = [5, 9, 3, 0, 1];
+/// let     w = cycle_sort_pinned(&mut a, &[1, 3]);
+///
+/// assert_eq!(a, [1, 9, 3, 0, 5]);
+/// assert_eq!(w, 2);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_pinned<T>(slice: &mut [T], pinned: &[usize]) -> usize
+where
+    T: Ord,
+{
+    let length = slice.len();
+
+    assert!(
+        pinned.windows(2).all(|w| w[0] < w[1]),
+        "pinned positions must be in strictly ascending order"
+    );
+    if let Some(&last) = pinned.last() {
+        assert!(
+            last < length,
+            "pinned position {} out of range for slice of length {}",
+            last,
+            length
+        );
+    }
+
+    let mut view = Pinned { buf: slice, pinned };
+
+    cycle_impl_from(&mut view, 0, &|a: &T, b: &T| a.lt(b))
+}
+
+/// Sorts every row of a row-major matrix independently and returns the
+/// total number of writes made.
+///
+/// # Panics
+///
+/// Panics if `cols` is zero or doesn't divide `data.len()`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_rows;
+/// let mut m = [
+///     3, 1, 2,
+///     4, 5, 6,
+///     9, 7, 8,
+/// ];
+/// let w = cycle_sort_rows(&mut m, 3);
+///
+/// assert_eq!(m, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+/// assert_eq!(w, 6);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_rows<T>(data: &mut [T], cols: usize) -> usize
+where
+    T: Ord,
+{
+    assert!(cols != 0, "number of columns must be non-zero");
+    assert!(
+        data.len().is_multiple_of(cols),
+        "length {} is not a multiple of {} columns",
+        data.len(),
+        cols
+    );
+
+    data.chunks_exact_mut(cols).map(cycle_sort).sum()
+}
+
+/// Sorts every column of a row-major matrix independently and returns the
+/// total number of writes made.
+///
+/// # Panics
+///
+/// Panics if `cols` is zero or doesn't divide `data.len()`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_columns;
+/// let mut m = [
+///     3, 5, 8,
+///     1, 4, 9,
+///     2, 6, 7,
+/// ];
+/// let w = cycle_sort_columns(&mut m, 3);
+///
+/// assert_eq!(m, [1, 4, 7, 2, 5, 8, 3, 6, 9]);
+/// assert_eq!(w, 8);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_columns<T>(data: &mut [T], cols: usize) -> usize
+where
+    T: Ord,
+{
+    assert!(cols != 0, "number of columns must be non-zero");
+    assert!(
+        data.len().is_multiple_of(cols),
+        "length {} is not a multiple of {} columns",
+        data.len(),
+        cols
+    );
+
+    let rows = data.len() / cols;
+
+    (0..cols)
+        .map(|start| {
+            let mut view = Strided {
+                buf: &mut *data,
+                start,
+                stride: cols,
+                len: rows,
+            };
+
+            cycle_impl_from(&mut view, 0, &|a: &T, b: &T| a.lt(b))
+        })
+        .sum()
+}
+
+/// Sorts every slice in a batch independently and returns the total number
+/// of writes made.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_batch;
+/// let mut a = [3, 1, 2];
+/// let mut b = [1, 2];
+/// let mut c = [0; 0];
+/// let     w = cycle_sort_batch(&mut [&mut a[..], &mut b, &mut c]);
+///
+/// assert_eq!(a, [1, 2, 3]);
+/// assert_eq!(w, 3);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_batch<T>(slices: &mut [&mut [T]]) -> usize
+where
+    T: Ord,
+{
+    slices.iter_mut().map(|slice| cycle_sort(slice)).sum()
+}
+
+/// Sorts every bucket of a slice independently, storing the number of writes
+/// made in each one in `writes_out`, and returns the total number of writes
+/// made.
+///
+/// `bucket_bounds` holds the positions every bucket but the first starts
+/// at, just like the segment bounds of [`cycle_sort_segmented`], so `k`
+/// bounds delimit `k + 1` buckets, which may be empty, and `writes_out`
+/// must have room for all of them.
+///
+/// [`cycle_sort_segmented`]: fn.cycle_sort_segmented.html
+///
+/// # Panics
+///
+/// Panics if `bucket_bounds` is not in ascending order, holds a position
+/// greater than `slice.len()`, or if `writes_out` doesn't have exactly one
+/// more element than `bucket_bounds`.
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_buckets;
+/// let mut a = [3, 1, 2, 5, 4, 6];
+/// let mut b = [0; 3];
+/// let     w = cycle_sort_buckets(&mut a, &[3, 5], &mut b);
+///
+/// assert_eq!(a, [1, 2, 3, 4, 5, 6]);
+/// assert_eq!(b, [3, 2, 0]);
+/// assert_eq!(w, 5);
+/// ```
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_buckets<T>(
+    slice: &mut [T],
+    bucket_bounds: &[usize],
+    writes_out: &mut [usize],
+) -> usize
+where
+    T: Ord,
+{
+    let length = slice.len();
+
+    assert!(
+        bucket_bounds.windows(2).all(|w| w[0] <= w[1]),
+        "bucket bounds must be in ascending order"
+    );
+    if let Some(&last) = bucket_bounds.last() {
+        assert!(
+            last <= length,
+            "bucket bound {} out of range for slice of length {}",
+            last,
+            length
+        );
+    }
+    assert_eq!(
+        writes_out.len(),
+        bucket_bounds.len() + 1,
+        "writes_out length must be one more than the number of bucket bounds"
+    );
+
+    let starts = iter::once(0).chain(bucket_bounds.iter().copied());
+    let ends = bucket_bounds.iter().copied().chain(iter::once(length));
+
+    starts
+        .zip(ends)
+        .zip(writes_out)
+        .map(|((start, end), out)| {
+            *out = cycle_sort(&mut slice[start..end]);
+            *out
+        })
+        .sum()
+}
+
+/// Sorts the elements of a [`SmallVec`] using their natural ordering and
+/// returns the number of writes made.
+///
+/// This is the same as sorting `v.as_mut_slice()` with [`cycle_sort`],
+/// whether the elements are stored inline or on the heap. Requires the
+/// `smallvec` feature.
+///
+/// [`SmallVec`]: https://docs.rs/smallvec/1/smallvec/struct.SmallVec.html
+/// [`cycle_sort`]: fn.cycle_sort.html
+///
+/// # Examples
+///
+/// ```
+/// # use cycle_sort::cycle_sort_smallvec;
+/// use smallvec::{smallvec, SmallVec};
+///
+/// let mut v: SmallVec<[u8; 4]> = smallvec![3, 1, 2];
+/// let     w = cycle_sort_smallvec(&mut v);
+///
+/// assert_eq!(&v[..], [1, 2, 3]);
+/// assert_eq!(w, 3);
+/// ```
+#[cfg(feature = "smallvec")]
+#[must_use = "the write count is the primary result; ignore with let _ = if intentional"]
+#[inline]
+pub fn cycle_sort_smallvec<A>(v: &mut smallvec::SmallVec<A>) -> usize
+where
+    A: smallvec::Array,
+    A::Item: Ord,
+{
+    cycle_sort(v.as_mut_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn zero_sized_elements() {
+        const SIZE: usize = 1100;
+
+        let mut array = [(); SIZE];
+
+        for length in (0..10).chain(1000..SIZE + 1) {
+            assert_eq!(cycle_sort_wrapped(&mut array, SIZE - 1, length), 0);
+            assert_eq!(cycle_sort_rows(&mut array[..length], 1), 0);
+            assert_eq!(cycle_sort_columns(&mut array[..length], 1), 0);
+            assert_eq!(cycle_sort_bitonic(&mut array[..length]), 0);
+            assert_eq!(cycle_sort_where(&mut array[..length], &|_| false), 0);
+            assert_eq!(cycle_sort_pinned(&mut array[..length], &[]), 0);
+
+            let (a, b) = array[..length].split_at_mut(length / 2);
+            assert_eq!(cycle_sort_batch(&mut [a, b]), 0);
+
+            let mut out = [1; 2];
+            assert_eq!(
+                cycle_sort_buckets(&mut array[..length], &[length / 2], &mut out),
+                0
+            );
+            assert_eq!(out, [0; 2]);
+        }
+    }
+
+    #[test]
+    fn wrapped_matches_contiguous() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for start in 0..SIZE {
+            for len in 0..SIZE + 1 {
+                let mut buf = [0_u8; SIZE];
+                rng.fill(&mut buf[..]);
+
+                // copy out the logical sequence and sort it the normal way
+                let mut expect = [0_u8; SIZE];
+                for (i, x) in expect[..len].iter_mut().enumerate() {
+                    *x = buf[(start + i) % SIZE];
+                }
+                let before = buf;
+                let writes = cycle_sort(&mut expect[..len]);
+
+                assert_eq!(cycle_sort_wrapped(&mut buf, start, len), writes);
+
+                for i in 0..SIZE {
+                    let logical = (i + SIZE - start) % SIZE;
+
+                    if logical < len {
+                        assert_eq!(buf[i], expect[logical]);
+                    } else {
+                        assert_eq!(buf[i], before[i]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bitonic() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+            let before = a;
+
+            let mut expect = a;
+            expect[..length].sort();
+            expect[length.div_ceil(2)..length].reverse();
+
+            let writes = cycle_sort_bitonic(&mut a[..length]);
+            let changed = (0..length).filter(|&i| before[i] != expect[i]).count();
+
+            assert_eq!(a, expect);
+            assert_eq!(writes, changed);
+        }
+    }
+
+    #[test]
+    fn sort_where() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+            let before = a;
+
+            for &modulus in &[1, 2, 3, 17] {
+                // interleaved fixed and movable elements
+                let keep_fixed = |x: &u8| x % modulus == 1;
+                let mut b = before;
+
+                // sort the movable elements separately and put them back
+                let mut movable = [0_u8; SIZE];
+                let n = before[..length].iter().filter(|x| !keep_fixed(x)).count();
+                for (m, &x) in movable
+                    .iter_mut()
+                    .zip(before[..length].iter().filter(|x| !keep_fixed(x)))
+                {
+                    *m = x;
+                }
+                let expect = cycle_sort(&mut movable[..n]);
+
+                let mut c = before;
+                for (x, &m) in c[..length]
+                    .iter_mut()
+                    .filter(|x| !keep_fixed(x))
+                    .zip(&movable)
+                {
+                    *x = m;
+                }
+
+                let writes = cycle_sort_where(&mut b[..length], &keep_fixed);
+
+                assert_eq!(writes, expect);
+                assert_eq!(b, c);
+            }
+        }
+    }
+
+    #[test]
+    fn pinned() {
+        const SIZE: usize = 40;
+
+        let mut rng = thread_rng();
+
+        for length in 0..SIZE {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..length]);
+            for x in a.iter_mut() {
+                *x %= 16;
+            }
+
+            for &modulus in &[1, 2, 3, 7] {
+                // pin every `modulus`:th position, and the first and last
+                let mut is_pinned = [false; SIZE];
+                for (i, p) in is_pinned[..length].iter_mut().enumerate() {
+                    *p = i % modulus == 1 || (modulus > 2 && (i == 0 || i + 1 == length));
+                }
+                let mut pinned = [0; SIZE];
+                let mut k = 0;
+                for i in (0..length).filter(|&i| is_pinned[i]) {
+                    pinned[k] = i;
+                    k += 1;
+                }
+
+                // sort the rest separately and put them back
+                let mut free = [0_u8; SIZE];
+                let n = length - k;
+                for (f, i) in free.iter_mut().zip((0..length).filter(|&i| !is_pinned[i])) {
+                    *f = a[i];
+                }
+                let expect = cycle_sort(&mut free[..n]);
+
+                let mut c = a;
+                for (i, &f) in (0..length).filter(|&i| !is_pinned[i]).zip(&free) {
+                    c[i] = f;
+                }
+
+                let mut b = a;
+                let writes = cycle_sort_pinned(&mut b[..length], &pinned[..k]);
+
+                assert_eq!(writes, expect);
+                assert_eq!(b, c);
+            }
+        }
+    }
+
+    #[test]
+    fn rows() {
+        const SIZE: usize = 120;
+
+        let mut rng = thread_rng();
+
+        for &cols in &[1, 2, 3, 8, 40, 120] {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..]);
+            let mut b = a;
+
+            let writes = cycle_sort_rows(&mut a, cols);
+            let expect: usize = b.chunks_mut(cols).map(cycle_sort).sum();
+
+            assert_eq!(writes, expect);
+            assert_eq!(a, b);
+        }
+
+        assert_eq!(cycle_sort_rows::<u8>(&mut [], 3), 0);
+    }
+
+    #[test]
+    fn columns() {
+        const SIZE: usize = 120;
+
+        let mut rng = thread_rng();
+
+        for &cols in &[1, 2, 3, 8, 40, 120] {
+            let rows = SIZE / cols;
+
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..]);
+
+            // sort the transposed matrix by rows instead
+            let mut t = [0_u8; SIZE];
+            for (i, x) in t.iter_mut().enumerate() {
+                *x = a[i % rows * cols + i / rows];
+            }
+
+            let writes = cycle_sort_columns(&mut a, cols);
+            let expect = cycle_sort_rows(&mut t, rows);
+
+            assert_eq!(writes, expect);
+
+            for (i, x) in t.iter().enumerate() {
+                assert_eq!(a[i % rows * cols + i / rows], *x);
+            }
+        }
+
+        assert_eq!(cycle_sort_columns::<u8>(&mut [], 3), 0);
+    }
+
+    #[test]
+    fn batch() {
+        const SIZE: usize = 120;
+
+        let mut rng = thread_rng();
+
+        let mut a = [0_u8; SIZE];
+        rng.fill(&mut a[..]);
+        a[20..40].sort_unstable();
+        let mut b = a;
+
+        // empty, sorted and random slices
+        let (empty, rest) = a.split_at_mut(0);
+        let (random, rest) = rest.split_at_mut(20);
+        let (sorted, rest) = rest.split_at_mut(20);
+        let (single, rest) = rest.split_at_mut(1);
+        let writes = cycle_sort_batch(&mut [empty, random, sorted, single, rest]);
+
+        let expect =
+            cycle_sort(&mut b[..20]) + cycle_sort(&mut b[20..40]) + cycle_sort(&mut b[41..]);
+
+        assert_eq!(writes, expect);
+        assert_eq!(a, b);
+
+        assert_eq!(cycle_sort_batch::<u8>(&mut []), 0);
+    }
+
+    #[test]
+    fn buckets() {
+        const SIZE: usize = 60;
+
+        let mut rng = thread_rng();
+
+        for _ in 0..20 {
+            let mut a = [0_u8; SIZE];
+            rng.fill(&mut a[..]);
+            let mut b = a;
+
+            let mut bounds = [0; 4];
+            for x in bounds.iter_mut() {
+                *x = rng.gen_range(0, SIZE + 1);
+            }
+            bounds.sort_unstable();
+
+            let mut out = [0; 5];
+            let writes = cycle_sort_buckets(&mut a, &bounds, &mut out);
+
+            let mut start = 0;
+            for (i, &end) in bounds.iter().chain(&[SIZE]).enumerate() {
+                assert_eq!(out[i], cycle_sort(&mut b[start..end]));
+                start = end;
+            }
+
+            assert_eq!(writes, out.iter().sum::<usize>());
+            assert_eq!(a, b);
+        }
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn smallvec() {
+        use smallvec::SmallVec;
+
+        let mut rng = thread_rng();
+
+        for length in 0..20 {
+            let mut a: SmallVec<[u8; 8]> = (0..length).map(|_| rng.gen()).collect();
+            let mut b = a.clone();
+
+            let expect = cycle_sort(&mut b[..]);
+
+            assert_eq!(cycle_sort_smallvec(&mut a), expect);
+            assert_eq!(a, b);
+        }
+
+        let mut z: SmallVec<[(); 4]> = SmallVec::from_elem((), 1100);
+        assert_eq!(cycle_sort_smallvec(&mut z), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rows_uneven() {
+        let _ = cycle_sort_rows(&mut [1, 2, 3], 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn columns_uneven() {
+        let _ = cycle_sort_columns(&mut [1, 2, 3], 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn length_out_of_range() {
+        let _ = cycle_sort_wrapped(&mut [1, 2, 3], 0, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "bucket bounds must be in ascending order")]
+    fn buckets_descending() {
+        let _ = cycle_sort_buckets(&mut [1, 2, 3], &[2, 1], &mut [0; 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bucket bound 4 out of range for slice of length 3")]
+    fn buckets_out_of_range() {
+        let _ = cycle_sort_buckets(&mut [1, 2, 3], &[4], &mut [0; 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "pinned positions must be in strictly ascending order")]
+    fn pinned_duplicates() {
+        let _ = cycle_sort_pinned(&mut [3, 2, 1], &[1, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "pinned position 3 out of range for slice of length 3")]
+    fn pinned_out_of_range() {
+        let _ = cycle_sort_pinned(&mut [3, 2, 1], &[0, 3]);
+    }
+}
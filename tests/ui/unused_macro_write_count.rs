@@ -0,0 +1,11 @@
+#![deny(unused_must_use)]
+
+#[macro_use]
+extern crate cycle_sort;
+
+cycle_sort_for!(sort_u8: u8);
+
+fn main() {
+    let mut a = [3, 1, 2];
+    sort_u8(&mut a);
+}
@@ -0,0 +1,8 @@
+#![deny(unused_must_use)]
+
+use cycle_sort::cycle_sort;
+
+fn main() {
+    let mut a = [3, 1, 2];
+    cycle_sort(&mut a);
+}